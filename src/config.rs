@@ -0,0 +1,365 @@
+use crate::decomments::{Comment, Quote};
+use crate::error::Error;
+use crate::language::{Language, Registry};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Names of the configuration files looked up when none is given explicitly, in order.
+pub const CONFIG_FILES: [&str; 2] = [".removecommentary.toml", ".removecommentary.json"];
+
+/// Comment and string rules of a language declared by the user rather than built into the crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Syntax {
+    pub name: String,
+    pub aliases: Vec<String>, // other names used by shebang lines and modelines
+    pub extensions: Vec<String>,
+    pub files: Vec<String>, // glob patterns matched against file names, or against paths under `root` if they contain a '/'
+    pub root: PathBuf,      // directory of the configuration file, or empty for the working directory
+    pub comments: Box<[Comment]>,
+    pub quotes: Box<[Quote]>,
+}
+
+impl Language for Syntax {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn comments(&self) -> Box<[Comment]> {
+        self.comments.clone()
+    }
+
+    fn quotes(&self) -> Box<[Quote]> {
+        self.quotes.clone()
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) || self.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let by_extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.extensions.iter().any(|x| x.trim_start_matches('.') == e));
+        by_extension
+            || self.files.iter().any(|pattern| {
+                let subject = match pattern.contains('/') {
+                    true => relative(path, &self.root),
+                    false => path.file_name().and_then(|n| n.to_str()).map(str::to_string),
+                };
+                subject.is_some_and(|s| glob(pattern, &s))
+            })
+    }
+}
+
+// Returns `path` relative to `root`, with '/' separators, if it lies under it. `.` components are
+// ignored, so that `./a/b` lies under `a`.
+fn relative(path: &Path, root: &Path) -> Option<String> {
+    let components = |path: &Path| path.components().filter(|c| *c != Component::CurDir).collect::<PathBuf>();
+    let path = components(path);
+    let rest = path.strip_prefix(components(root)).ok()?;
+    let parts: Option<Vec<&str>> = rest.components().map(|c| c.as_os_str().to_str()).collect();
+    Some(parts?.join("/"))
+}
+
+/// Languages declared in a configuration file.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub languages: Vec<Arc<Syntax>>,
+}
+
+impl Config {
+    /// Reads a configuration file, in JSON if its extension is `.json` and in TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| Error::from(e).in_file(path))?;
+        let mut config = match path.extension().is_some_and(|e| e == "json") {
+            true => Self::from_json(&text),
+            false => Self::from_toml(&text),
+        }
+        .map_err(|e| e.in_file(path))?;
+        // paths in `files` patterns are relative to the directory of the configuration file
+        let root = path.parent().unwrap_or(Path::new(""));
+        for syntax in &mut config.languages {
+            Arc::make_mut(syntax).root = root.to_path_buf();
+        }
+        Ok(config)
+    }
+
+    /// Looks for one of the [`CONFIG_FILES`] in `dir`.
+    pub fn find(dir: impl AsRef<Path>) -> Option<Result<Self, Error>> {
+        CONFIG_FILES
+            .iter()
+            .map(|name| dir.as_ref().join(name))
+            .find(|path| path.is_file())
+            .map(Self::load)
+    }
+
+    /// Parses a configuration written in TOML.
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        let file: File = toml::from_str(text).map_err(|e| Error::Config { file: None, message: e.to_string() })?;
+        file.into_config()
+    }
+
+    /// Parses a configuration written in JSON.
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        let file: File = serde_json::from_str(text).map_err(|e| Error::Config { file: None, message: e.to_string() })?;
+        file.into_config()
+    }
+
+    /// Adds the declared languages to `registry`, where the first one declared takes precedence.
+    pub fn register(&self, registry: &mut Registry) {
+        for syntax in self.languages.iter().rev() {
+            registry.register_shared(syntax.clone());
+        }
+    }
+}
+
+// Layout of a configuration file:
+//
+// [[language]]
+// name = "Nim"
+// aliases = ["nimscript"]
+// extensions = ["nim"]
+// files = ["*.nimble"]
+// line_comments = ["#", { open = "##", doc = true }]
+// block_comments = [{ open = "#[", close = "]#", nests = true }]
+// strings = [{ open = '"' }, { open = 'r"', close = '"', raw = true }]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default, rename = "language")]
+    languages: Vec<Declared>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Declared {
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    line_comments: Vec<LineComment>,
+    #[serde(default)]
+    block_comments: Vec<BlockComment>,
+    #[serde(default)]
+    strings: Vec<Str>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LineComment {
+    Open(String),
+    Rule(LineRule),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LineRule {
+    open: String,
+    #[serde(default)]
+    doc: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BlockComment {
+    open: String,
+    close: String,
+    #[serde(default)]
+    nests: bool,
+    #[serde(default)]
+    doc: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Str {
+    open: String,
+    close: Option<String>, // defaults to `open`
+    #[serde(default = "backslash")]
+    escape: char,
+    #[serde(default)]
+    raw: bool, // whether escapes are not recognised
+}
+
+fn backslash() -> char {
+    '\\'
+}
+
+impl File {
+    fn into_config(self) -> Result<Config, Error> {
+        let languages = self.languages.into_iter().map(Declared::into_syntax).collect::<Result<_, _>>()?;
+        Ok(Config { languages })
+    }
+}
+
+impl Declared {
+    fn into_syntax(self) -> Result<Arc<Syntax>, Error> {
+        let invalid = |message: String| Error::Config { file: None, message: format!("language \"{}\": {}", self.name, message) };
+
+        let mut comments = Vec::new();
+        for rule in &self.line_comments {
+            let (open, doc) = match rule {
+                LineComment::Open(open) => (open, false),
+                LineComment::Rule(LineRule { open, doc }) => (open, *doc),
+            };
+            comments.push(Comment {
+                open_pat: Cow::Owned(open.clone()),
+                close_pat: Cow::Borrowed("\n"),
+                nests: false,
+                keep_close_pat: true,
+                allow_close_pat: true,
+                doc,
+            });
+        }
+        for rule in &self.block_comments {
+            comments.push(Comment {
+                open_pat: Cow::Owned(rule.open.clone()),
+                close_pat: Cow::Owned(rule.close.clone()),
+                nests: rule.nests,
+                keep_close_pat: false,
+                allow_close_pat: false,
+                doc: rule.doc,
+            });
+        }
+        let mut quotes: Vec<Quote> = self
+            .strings
+            .iter()
+            .map(|rule| Quote {
+                open: Cow::Owned(rule.open.clone()),
+                close: Cow::Owned(rule.close.clone().unwrap_or_else(|| rule.open.clone())),
+                escape: (!rule.raw).then_some(rule.escape),
+            })
+            .collect();
+
+        if let Some(empty) = comments.iter().map(|c| &c.open_pat).chain(quotes.iter().map(|q| &q.open)).find(|p| p.is_empty()) {
+            return Err(invalid(format!("empty delimiter {:?}", empty)));
+        }
+        if quotes.iter().any(|q| q.close.is_empty()) || comments.iter().any(|c| c.close_pat.is_empty()) {
+            return Err(invalid("empty closing delimiter".to_string()));
+        }
+
+        // The longest delimiter wins, so that `##` is not taken for `#` followed by `#`.
+        comments.sort_by_key(|c| std::cmp::Reverse(c.open_pat.len()));
+        quotes.sort_by_key(|q| std::cmp::Reverse(q.open.len()));
+
+        Ok(Arc::new(Syntax {
+            name: self.name,
+            aliases: self.aliases,
+            extensions: self.extensions,
+            files: self.files,
+            root: PathBuf::new(),
+            comments: comments.into_boxed_slice(),
+            quotes: quotes.into_boxed_slice(),
+        }))
+    }
+}
+
+// Matches `name` against a glob pattern in which `*` stands for any run of characters and `?` for
+// any single character.
+pub(crate) fn glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None; // position of the last `*` and of the text it currently ends at
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, end)) => {
+                    p = star + 1;
+                    n = end + 1;
+                    backtrack = Some((star, end + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob, Config};
+    use crate::{Language, Registry, Stripper};
+    use std::fs;
+    use std::path::Path;
+
+    const NIM: &str = "[[language]]\nname = \"Nim\"\nextensions = [\"nim\"]\nfiles = [\"*.nimble\"]\n\
+        line_comments = [\"#\", { open = \"##\", doc = true }]\n\
+        block_comments = [{ open = \"#[\", close = \"]#\", nests = true }]\n\
+        strings = [{ open = '\"' }]\n";
+
+    #[test]
+    fn globs() {
+        assert!(glob("*.cfg", "a.cfg"));
+        assert!(glob("a?c*", "abc"));
+        assert!(glob("*a*b", "xaayb"));
+        assert!(!glob("*.cfg", "a.cfgx"));
+        assert!(!glob("a?c", "ac"));
+    }
+
+    #[test]
+    fn languages() {
+        let config = Config::from_toml(NIM).unwrap();
+        let mut registry = Registry::empty();
+        config.register(&mut registry);
+        assert!(registry.detect(Path::new("src/a.nim")).is_some());
+        assert!(registry.detect(Path::new("a.nimble")).is_some());
+        assert!(registry.detect(Path::new("a.rs")).is_none());
+
+        let stripper = Stripper::shared(config.languages[0].clone());
+        let src = "echo \"#\" # c\n#[ a #[ b ]# ]#x ## d\n";
+        assert_eq!(stripper.strip_str(src).unwrap(), "echo \"#\" \nx \n");
+        assert_eq!(stripper.keep_docs(true).strip_str(src).unwrap(), "echo \"#\" \nx ## d\n");
+
+        let json = Config::from_json("{\"language\": [{\"name\": \"Nim\", \"extensions\": [\".nim\"]}]}").unwrap();
+        let mut registry = Registry::default();
+        json.register(&mut registry);
+        assert_eq!(registry.detect(Path::new("a.nim")).unwrap().name(), "Nim");
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert!(Config::from_toml("[[language]]\nname = \"A\"\nline_comments = [\"\"]\n").is_err());
+        assert!(Config::from_toml("[[language]]\nname = \"A\"\nstrings = [{ open = '\"', close = \"\" }]\n").is_err());
+        assert!(Config::from_toml("[[language]]\nname = \"A\"\ncolor = \"red\"\n").is_err());
+    }
+
+    #[test]
+    fn line_comment_rules() {
+        let config = |rule: &str| Config::from_toml(&format!("[[language]]\nname = \"Nim\"\nline_comments = [\"#\", {}]\n", rule));
+        assert!(config("{ open = \"##\", doc = true }").is_ok());
+        assert!(config("{ open = \"##\", docs = true }").is_err());
+    }
+
+    #[test]
+    fn path_patterns() {
+        let config = Config::from_toml("[[language]]\nname = \"Cfg\"\nfiles = [\"config/*.cfg\"]\n").unwrap();
+        assert!(config.languages[0].matches(Path::new("config/a.cfg")));
+        assert!(config.languages[0].matches(Path::new("./config/a.cfg")));
+        assert!(!config.languages[0].matches(Path::new("src/config/a.cfg")));
+
+        let dir = std::env::temp_dir().join(format!("remove-commentary-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".removecommentary.toml"), "[[language]]\nname = \"Cfg\"\nfiles = [\"config/*.cfg\"]\n").unwrap();
+        let config = Config::find(&dir).unwrap().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(config.languages[0].matches(&dir.join("config/a.cfg")));
+        assert!(!config.languages[0].matches(&dir.join("src/config/a.cfg")));
+    }
+}
@@ -1,336 +1,550 @@
-use std::collections::VecDeque;
-use derive_more::Deref;
-use std::fs::File;
-use std::io::Read;
-
+use crate::config::glob;
+use crate::error::Error;
+use crate::language::Language;
+use crate::lexer::{CommentKind, Cursor, Event, Fault, Lexer, Scan, Table, Token};
+use crate::verify;
+use std::borrow::Cow;
+use std::path::Path;
+use std::str::Chars;
+
+mod buildfile;
+mod c;
+mod configfile;
+mod csharp;
+mod css;
+mod go;
+mod haskell;
+mod javascript;
+mod jvm;
+mod lisp;
+mod markup;
+mod php;
+mod python;
+mod rust;
+mod ruby;
+mod shell;
+mod sql;
+
+pub use sql::Dialect;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Type {
-    RustC, Python, Haskell, Markup
+    Rust, C, Cpp, CSharp,
+    Java, Kotlin, Groovy, Scala,
+    JavaScript, TypeScript, Tsx, Go,
+    Python, Haskell, LiterateHaskell,
+    Markup, Xml, Vue, Svelte, Astro,
+    Make, Dockerfile, CMake, Shell,
+    Sql(Dialect), Lua, Ruby, Php,
+    Css, Scss, Less, Sass,
+    Yaml, Toml, Ini, Properties, Dotenv,
+    CommonLisp, Scheme, Racket, Clojure, ClojureScript, Edn, EmacsLisp,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Comment {
-    pub open_pat: &'static str, // pat = pattern
-    pub close_pat: &'static str,
+    pub open_pat: Cow<'static, str>, // pat = pattern
+    pub close_pat: Cow<'static, str>,
     pub nests: bool,
     pub keep_close_pat: bool, // whether to still return close_pat as part of the text
     pub allow_close_pat: bool, // whether to allow close_pat without matching open_pat
+    pub doc: bool, // whether this is a documentation comment
 }
 
-// Single-line comments shared by multiple languages.
+// Single-line comments, which the others are derived from.
 const SL_COMMENT: Comment = Comment {
-    open_pat: "//",
-    close_pat: "\n",
+    open_pat: Cow::Borrowed("//"),
+    close_pat: Cow::Borrowed("\n"),
     nests: false,
     keep_close_pat: true,
     allow_close_pat: true,
+    doc: false,
 };
 
-// Block comments for Rust and CPP are the same, so they can be reused.
-const BLOCK_COMMENT: Comment = Comment {
-    open_pat: "/*",
-    close_pat: "*/",
-    nests: false,
-    keep_close_pat: false,
-    allow_close_pat: false,
-};
+impl Comment {
+    // Classifies the comments matched by this pattern.
+    pub fn kind(&self) -> CommentKind {
+        if self.doc {
+            CommentKind::Doc
+        } else if self.keep_close_pat {
+            CommentKind::Line
+        } else {
+            CommentKind::Block
+        }
+    }
+}
 
-const RUSTC: [Comment; 2] = [SL_COMMENT, BLOCK_COMMENT];
+// String literals running from `open` to `close`, in which `escape` protects the next character.
+// A string without an escape character is raw.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quote {
+    pub open: Cow<'static, str>,
+    pub close: Cow<'static, str>,
+    pub escape: Option<char>,
+}
 
-const PYTHON: [Comment; 3] = [
-    Comment {
-        open_pat: "#",
-        close_pat: "\n",
-        nests: false,
-        keep_close_pat: true,
-        allow_close_pat: true,
-    },
-    // String literals for Python that can act as multi-line comments
-    Comment {
-        open_pat: "'''",
-        close_pat: "'''",
-        nests: false,
-        keep_close_pat: false,
-        allow_close_pat: false,
-    },
-    Comment {
-        open_pat: "\"\"\"",
-        close_pat: "\"\"\"",
-        nests: false,
-        keep_close_pat: false,
-        allow_close_pat: false,
-    },
+// Strings recognised by default: Markdown-style fences are opaque, quotes use backslash escapes.
+pub(crate) const QUOTES: [Quote; 3] = [
+    Quote { open: Cow::Borrowed("```"), close: Cow::Borrowed("```"), escape: None },
+    Quote { open: Cow::Borrowed("\""), close: Cow::Borrowed("\""), escape: Some('\\') },
+    Quote { open: Cow::Borrowed("'"), close: Cow::Borrowed("'"), escape: Some('\\') },
 ];
 
-const HASKELL: [Comment; 2] = [
+// Comments running from `#` to the end of the line.
+const HASH: [Comment; 1] = [
     Comment {
-        open_pat: "--",
-        close_pat: "\n",
+        open_pat: Cow::Borrowed("#"),
+        close_pat: Cow::Borrowed("\n"),
         nests: false,
         keep_close_pat: true,
         allow_close_pat: true,
+        doc: false,
     },
-    Comment {
-        open_pat: "{-",
-        close_pat: "-}",
-        nests: true,
-        keep_close_pat: false,
-        allow_close_pat: false,
-    },
 ];
 
-const MARKUP: [Comment; 1] = [
+// Multi-line strings must be matched before the single-line ones; literal strings know no escapes.
+const TOML_QUOTES: [Quote; 4] = [
+    Quote { open: Cow::Borrowed("\"\"\""), close: Cow::Borrowed("\"\"\""), escape: Some('\\') },
+    Quote { open: Cow::Borrowed("'''"), close: Cow::Borrowed("'''"), escape: None },
+    Quote { open: Cow::Borrowed("\""), close: Cow::Borrowed("\""), escape: Some('\\') },
+    Quote { open: Cow::Borrowed("'"), close: Cow::Borrowed("'"), escape: None },
+];
+
+// Long comments close with a bracket of the level they opened with, as in `--[==[ ... ]==]`; since
+// their closing pattern can only follow an opening one, `]]` alone is code.
+const LUA: [Comment; 3] = [
     Comment {
-        open_pat: "<!--",
-        close_pat: "-->",
+        open_pat: Cow::Borrowed("--[{=}["),
+        close_pat: Cow::Borrowed("]{=}]"),
         nests: false,
         keep_close_pat: false,
-        allow_close_pat: false,
+        allow_close_pat: true,
+        doc: false,
     },
+    Comment { open_pat: Cow::Borrowed("---"), doc: true, ..SL_COMMENT },
+    Comment { open_pat: Cow::Borrowed("--"), ..SL_COMMENT },
 ];
 
-#[derive(Deref, Debug)]
-#[repr(transparent)]
-struct Buf(VecDeque<char>); // Defines a Buffer struct that contains a double-ended queue to hold characters.
+// Long strings such as `[[ ... ]]` and `[=[ ... ]=]` know no escapes.
+const LUA_QUOTES: [Quote; 3] = [
+    Quote { open: Cow::Borrowed("[{=}["), close: Cow::Borrowed("]{=}]"), escape: None },
+    Quote { open: Cow::Borrowed("\""), close: Cow::Borrowed("\""), escape: Some('\\') },
+    Quote { open: Cow::Borrowed("'"), close: Cow::Borrowed("'"), escape: Some('\\') },
+];
 
-impl Buf {
-    // Constructs a new buffer with a specified maximum length.
-    fn new(max_len: usize) -> Self {
-        Self(VecDeque::with_capacity(max_len))
+impl Type {
+    // Every built-in language, in the order they are matched against files.
+    pub(crate) const ALL: [Type; 48] = [
+        Type::Rust, Type::C, Type::Cpp, Type::CSharp,
+        Type::Java, Type::Kotlin, Type::Groovy, Type::Scala,
+        Type::JavaScript, Type::TypeScript, Type::Tsx, Type::Go,
+        Type::Python, Type::Haskell, Type::LiterateHaskell,
+        Type::Markup, Type::Xml, Type::Vue, Type::Svelte, Type::Astro,
+        Type::Make, Type::Dockerfile, Type::CMake, Type::Shell,
+        // the dialect-neutral profile comes last so that it takes `.sql` files
+        Type::Sql(Dialect::Postgres), Type::Sql(Dialect::MySql), Type::Sql(Dialect::Sqlite), Type::Sql(Dialect::TSql),
+        Type::Sql(Dialect::Standard), Type::Lua, Type::Ruby, Type::Php,
+        Type::Css, Type::Scss, Type::Less, Type::Sass,
+        Type::Yaml, Type::Toml, Type::Ini, Type::Properties, Type::Dotenv,
+        Type::CommonLisp, Type::Scheme, Type::Racket, Type::Clojure, Type::ClojureScript, Type::Edn, Type::EmacsLisp,
+    ];
+
+    // File extensions of this language.
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Type::Rust => &["rs"],
+            Type::C => &["c", "h"],
+            Type::Cpp => &["cc", "cpp", "cxx", "c++", "hh", "hpp", "hxx", "inl", "ipp"],
+            Type::CSharp => &["cs"],
+            Type::Java => &["java"],
+            Type::Kotlin => &["kt", "kts"],
+            Type::Groovy => &["groovy", "gradle"],
+            Type::Scala => &["scala", "sc"],
+            Type::JavaScript => &["js", "mjs", "cjs", "jsx"],
+            Type::TypeScript => &["ts", "mts", "cts"],
+            Type::Tsx => &["tsx"],
+            Type::Go => &["go"],
+            Type::Python => &["py", "pyw", "pyi"],
+            Type::Haskell => &["hs"],
+            Type::LiterateHaskell => &["lhs"],
+            Type::Markup => &["htm", "html", "xhtml"],
+            Type::Xml => &["xml", "svg", "xaml", "xsd", "xsl", "xslt", "csproj", "props", "targets", "plist"],
+            Type::Vue => &["vue"],
+            Type::Svelte => &["svelte"],
+            Type::Astro => &["astro"],
+            Type::Make => &["mk", "mak", "make"],
+            Type::Dockerfile => &["dockerfile", "containerfile"],
+            Type::CMake => &["cmake"],
+            Type::Shell => &["sh", "bash", "zsh", "ksh", "command"],
+            Type::Sql(Dialect::Postgres) => &["sql", "pgsql"],
+            Type::Sql(Dialect::MySql) => &["sql", "mysql"],
+            Type::Sql(_) => &["sql"],
+            Type::Lua => &["lua", "rockspec"],
+            Type::Ruby => &["rb", "rake", "gemspec", "ru"],
+            Type::Php => &["php", "phtml", "php3", "php4", "php5", "php7", "php8", "phps"],
+            Type::Css => &["css"],
+            Type::Scss => &["scss"],
+            Type::Less => &["less"],
+            Type::Sass => &["sass"],
+            Type::Yaml => &["yaml", "yml"],
+            Type::Toml => &["toml"],
+            Type::Ini => &["ini", "cfg", "inf"],
+            Type::Properties => &["properties"],
+            Type::Dotenv => &["env"],
+            Type::CommonLisp => &["lisp", "lsp", "cl", "asd"],
+            Type::Scheme => &["scm", "ss", "sld", "sps"],
+            Type::Racket => &["rkt", "rktl", "rktd"],
+            Type::Clojure => &["clj", "cljc", "bb"],
+            Type::ClojureScript => &["cljs"],
+            Type::Edn => &["edn"],
+            Type::EmacsLisp => &["el"],
+        }
     }
 
-    // Checks if the buffer is full (i.e., if its length equals its capacity).
-    fn is_full(&self) -> bool {
-        self.len() == self.capacity()
+    // Glob patterns of the well-known file names of this language.
+    fn file_names(self) -> &'static [&'static str] {
+        match self {
+            Type::Make => &["Makefile", "makefile", "GNUmakefile", "*.Makefile"],
+            Type::Dockerfile => &["Dockerfile", "Containerfile", "Dockerfile.*", "Containerfile.*"],
+            Type::CMake => &["CMakeLists.txt"],
+            Type::Ruby => &["Rakefile", "Gemfile", "Guardfile", "Vagrantfile", ".irbrc"],
+            Type::Toml => &["Pipfile"],
+            Type::Ini => &[".editorconfig", ".gitconfig", ".npmrc"],
+            Type::Dotenv => &[".env", ".env.*"],
+            Type::EmacsLisp => &[".emacs", "_emacs", ".gnus", ".abbrev_defs"],
+            Type::Shell => &[".bashrc", ".bash_profile", ".bash_logout", ".profile", ".zshrc", ".zshenv", ".zprofile", ".zlogin", ".kshrc"],
+            _ => &[],
+        }
     }
 
-    // Fills up the buffer with characters from the provided iterator until the buffer is full.
-    fn fill_up(&mut self, iter: &mut impl Iterator<Item = char>) {
-        while !self.is_full() { // Continue until the buffer is full.
-            if let Some(x) = iter.next() { // Retrieve the next character from the iterator.
-                self.0.push_back(x); // Append the character to the end of the buffer.
-            } else {
-                break; // If there are no more characters, exit the loop.
-            }
+    // Names that shebang interpreters and editor modelines use for this language, in lower case.
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Type::Rust => &["rs", "rust-script", "cargo"],
+            Type::C => &[],
+            Type::Cpp => &["cpp", "cxx"],
+            Type::CSharp => &["cs", "csharp", "dotnet-script"],
+            Type::Java => &["jshell"],
+            Type::Kotlin => &["kt", "kts", "kscript"],
+            Type::Groovy => &[],
+            Type::Scala => &["scala-cli", "amm"],
+            Type::JavaScript => &["js", "js2", "node", "nodejs", "bun"],
+            Type::TypeScript => &["ts", "ts-node", "tsx", "deno"],
+            Type::Tsx => &[],
+            Type::Go => &["golang", "gorun"],
+            Type::Python => &["py", "pypy"],
+            Type::Haskell => &["hs", "runhaskell", "runghc"],
+            Type::LiterateHaskell => &["lhs", "lhaskell", "literate-haskell"],
+            Type::Markup => &["htm", "xhtml"],
+            Type::Xml => &["nxml", "svg"],
+            Type::Vue | Type::Svelte | Type::Astro => &[],
+            Type::Make => &["make", "gmake", "makefile", "makefile-gmake"],
+            Type::Dockerfile => &["docker", "containerfile"],
+            Type::CMake => &[],
+            Type::Shell => &["sh", "bash", "zsh", "ksh", "dash", "ash", "mksh", "shell-script"],
+            Type::Sql(Dialect::Standard) => &["sql"],
+            Type::Sql(Dialect::Postgres) => &["postgres", "pgsql", "plpgsql"],
+            Type::Sql(Dialect::MySql) => &["mariadb"],
+            Type::Sql(Dialect::Sqlite) => &[],
+            Type::Sql(Dialect::TSql) => &["tsql", "mssql", "sqlserver"],
+            Type::Lua => &["luajit", "texlua"],
+            Type::Ruby => &["rb", "jruby", "irb"],
+            Type::Php => &["php-cli", "phtml"],
+            Type::Css | Type::Scss | Type::Less | Type::Sass => &[],
+            Type::Yaml => &["yml"],
+            Type::Toml => &[],
+            Type::Ini => &["dosini", "confini", "gitconfig", "editorconfig"],
+            Type::Properties => &["jproperties", "java-properties"],
+            Type::Dotenv => &["dotenv"],
+            Type::CommonLisp => &["lisp", "common-lisp", "sbcl", "clisp", "ecl", "ccl"],
+            Type::Scheme => &["guile", "chicken", "csi", "gsi", "chez", "chezscheme", "mit-scheme", "petite"],
+            Type::Racket => &[],
+            Type::Clojure => &["clj", "bb", "babashka"],
+            Type::ClojureScript => &["cljs"],
+            Type::Edn => &[],
+            Type::EmacsLisp => &["elisp", "emacs-lisp", "emacs"],
         }
     }
+}
 
-    // Checks if the starting characters in the buffer match the given pattern (pat).
-    fn matches(&self, pat: &str) -> bool {
-        // Iterates over the buffer, takes the number of characters that equals the pattern's length, and checks for equality.
-        self.iter().take(pat.len()).copied().eq(pat.chars())
+// Whether a `.h` header uses C++ constructs.
+fn is_cpp_header(content: &str) -> bool {
+    content.lines().map(str::trim_start).any(|line| {
+        ["class ", "namespace ", "template<", "template <", "using ", "public:", "private:", "protected:", "#include <iostream>"]
+            .iter()
+            .any(|start| line.starts_with(start))
+            || line.contains("std::")
+    })
+}
+
+impl Language for Type {
+    fn name(&self) -> &str {
+        match self {
+            Type::Rust => "Rust",
+            Type::C => "C",
+            Type::Cpp => "C++",
+            Type::CSharp => "C#",
+            Type::Java => "Java",
+            Type::Kotlin => "Kotlin",
+            Type::Groovy => "Groovy",
+            Type::Scala => "Scala",
+            Type::JavaScript => "JavaScript",
+            Type::TypeScript => "TypeScript",
+            Type::Tsx => "TSX",
+            Type::Go => "Go",
+            Type::Python => "Python",
+            Type::Haskell => "Haskell",
+            Type::LiterateHaskell => "Literate Haskell",
+            Type::Markup => "HTML",
+            Type::Xml => "XML",
+            Type::Vue => "Vue",
+            Type::Svelte => "Svelte",
+            Type::Astro => "Astro",
+            Type::Make => "Make",
+            Type::Dockerfile => "Dockerfile",
+            Type::CMake => "CMake",
+            Type::Shell => "Shell",
+            Type::Sql(Dialect::Standard) => "SQL",
+            Type::Sql(Dialect::Postgres) => "PostgreSQL",
+            Type::Sql(Dialect::MySql) => "MySQL",
+            Type::Sql(Dialect::Sqlite) => "SQLite",
+            Type::Sql(Dialect::TSql) => "T-SQL",
+            Type::Lua => "Lua",
+            Type::Ruby => "Ruby",
+            Type::Php => "PHP",
+            Type::Css => "CSS",
+            Type::Scss => "SCSS",
+            Type::Less => "Less",
+            Type::Sass => "Sass",
+            Type::Yaml => "YAML",
+            Type::Toml => "TOML",
+            Type::Ini => "INI",
+            Type::Properties => "Properties",
+            Type::Dotenv => "Dotenv",
+            Type::CommonLisp => "Common Lisp",
+            Type::Scheme => "Scheme",
+            Type::Racket => "Racket",
+            Type::Clojure => "Clojure",
+            Type::ClojureScript => "ClojureScript",
+            Type::Edn => "EDN",
+            Type::EmacsLisp => "Emacs Lisp",
+        }
     }
 
-    // Removes and returns the character from the front of the buffer.
-    fn pop_front(&mut self) -> char {
-        self.0.pop_front().unwrap()
+    fn is_named(&self, name: &str) -> bool {
+        self.name().eq_ignore_ascii_case(name) || self.aliases().contains(&name)
     }
 
-    // Removes the specified number of characters (n) from the front of the buffer.
-    fn pop_front_n(&mut self, n: usize) {
-        let _ = self.0.drain(..n);
+    // Returns the comment patterns that lex this language. The languages with a scanner of their own
+    // have none, since their comments depend on more than delimiters.
+    fn comments(&self) -> Box<[Comment]> {
+        match *self {
+            Type::Lua => LUA.to_vec().into_boxed_slice(),
+            Type::Toml => HASH.to_vec().into_boxed_slice(),
+            _ => Box::new([]),
+        }
     }
-}
 
+    fn quotes(&self) -> Box<[Quote]> {
+        match *self {
+            Type::Lua => LUA_QUOTES.to_vec().into_boxed_slice(),
+            Type::Toml => TOML_QUOTES.to_vec().into_boxed_slice(),
+            _ => QUOTES.to_vec().into_boxed_slice(),
+        }
+    }
 
-#[derive(Debug)]
-enum TriOpt<T> {
-    Some(T),
-    None,
-    Wait,
-}
+    // Creates the scanner that lexes source text of this language.
+    fn scanner(&self) -> Box<dyn Scan> {
+        match *self {
+            Type::Rust => Box::new(rust::Rust),
+            Type::C => Box::new(c::C::new(false)),
+            Type::Cpp => Box::new(c::C::new(true)),
+            Type::CSharp => Box::new(csharp::CSharp::new()),
+            Type::Java => Box::new(jvm::Jvm::new(jvm::Flavor::Java)),
+            Type::Kotlin => Box::new(jvm::Jvm::new(jvm::Flavor::Kotlin)),
+            Type::Groovy => Box::new(jvm::Jvm::new(jvm::Flavor::Groovy)),
+            Type::Scala => Box::new(jvm::Jvm::new(jvm::Flavor::Scala)),
+            // `<T>x` is a type assertion in TypeScript, and only a JSX element in TSX
+            Type::JavaScript | Type::Tsx => Box::new(javascript::JavaScript::new(true)),
+            Type::TypeScript => Box::new(javascript::JavaScript::new(false)),
+            Type::Go => Box::new(go::Go),
+            Type::Python => Box::new(python::Python::new()),
+            Type::Haskell => Box::new(haskell::Haskell),
+            Type::LiterateHaskell => Box::new(haskell::Literate::new()),
+            Type::Markup => Box::new(Regions::new(markup::Markup::new(markup::Flavor::Html))),
+            Type::Vue => Box::new(Regions::new(markup::Markup::new(markup::Flavor::Vue))),
+            Type::Svelte => Box::new(Regions::new(markup::Markup::new(markup::Flavor::Svelte))),
+            Type::Astro => Box::new(Regions::new(markup::Markup::new(markup::Flavor::Astro))),
+            Type::Xml => Box::new(markup::Xml::new()),
+            Type::Make => Box::new(buildfile::Make::new()),
+            Type::Dockerfile => Box::new(buildfile::Dockerfile::new()),
+            Type::CMake => Box::new(buildfile::CMake),
+            Type::Shell => Box::new(shell::Shell::new()),
+            Type::Sql(dialect) => Box::new(sql::Sql::new(dialect)),
+            Type::Ruby => Box::new(ruby::Ruby::new()),
+            Type::Php => Box::new(php::Php::new()),
+            Type::Css => Box::new(css::Css::new(css::Flavor::Css)),
+            Type::Scss => Box::new(css::Css::new(css::Flavor::Scss)),
+            Type::Less => Box::new(css::Css::new(css::Flavor::Less)),
+            Type::Sass => Box::new(css::Css::new(css::Flavor::Sass)),
+            Type::Yaml => Box::new(configfile::Yaml::new()),
+            Type::Ini => Box::new(configfile::Ini),
+            Type::Properties => Box::new(configfile::Properties::new()),
+            Type::Dotenv => Box::new(configfile::Dotenv),
+            Type::CommonLisp => Box::new(lisp::Lisp::new(lisp::Flavor::CommonLisp)),
+            Type::Scheme | Type::Racket => Box::new(lisp::Lisp::new(lisp::Flavor::Scheme)),
+            Type::Clojure | Type::ClojureScript | Type::Edn => Box::new(lisp::Lisp::new(lisp::Flavor::Clojure)),
+            Type::EmacsLisp => Box::new(lisp::Lisp::new(lisp::Flavor::EmacsLisp)),
+            Type::Lua | Type::Toml => Box::new(Table::new(self.comments(), self.quotes())),
+        }
+    }
 
-impl<T> From<Option<T>> for TriOpt<T> {
-    fn from(o: Option<T>) -> Self {
-        match o {
-            Some(t) => TriOpt::Some(t),
-            None => TriOpt::None,
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        path.extension().and_then(|e| e.to_str()).is_some_and(|e| self.extensions().contains(&e))
+            || self.file_names().iter().any(|pattern| glob(pattern, name))
+    }
+
+    fn sniff(&self, path: &Path, content: &str) -> bool {
+        let extension = path.extension().and_then(|e| e.to_str());
+        match (self, extension) {
+            (Type::Cpp, Some("h")) => is_cpp_header(content),
+            // Qt translation files share the extension of TypeScript
+            (Type::Xml, Some("ts")) => content.trim_start().starts_with("<?xml"),
+            _ => false,
         }
     }
+
+    fn verify(&self, input: &str, output: &str) -> Result<(), String> {
+        verify::verify(*self, input, output)
+    }
 }
 
-pub struct WithoutComments<I: Iterator<Item = char>> {
-    iter: I,
-    buf: Buf,
-    comments: Box<[Comment]>,
-    state: Option<(usize, Option<usize>)>,
-    in_string: bool, // Track whether it's within a string literal
-    string_delimiter: Option<char>, // Stores the delimiter of the current string
-    escape_next: bool, // For handling escaped characters
+// A language that hands parts of its input over to other languages, like HTML does with scripts.
+pub(crate) trait Host: Scan {
+    // Takes the region opened by the token just scanned, as its language and end offset.
+    fn region(&mut self) -> Option<(Type, usize)>;
 }
 
-impl<I: Iterator<Item = char>> WithoutComments<I> {
-    fn new(iter: I, comments: Box<[Comment]>, buf_len: usize) -> Self {
-        Self {
-            iter,
-            buf: Buf::new(buf_len),
-            comments,
-            state: None,
-            in_string: false,
-            string_delimiter: None,
-            escape_next: false
-        }
-    }
+// Scanner that lexes the regions opened by a host with the scanner of their own language,
+// which cannot see past the end of its region.
+pub(crate) struct Regions<H> {
+    host: H,
+    guest: Option<(Box<dyn Scan>, usize)>,
+}
 
-    fn next_(&mut self) -> TriOpt<char> {
-        // at least one element missing from previous call
-        self.buf.fill_up(&mut self.iter);
+impl<H: Host> Regions<H> {
+    pub(crate) fn new(host: H) -> Self {
+        Self { host, guest: None }
+    }
+}
 
-        if self.buf.is_empty() {
-            return TriOpt::None;
+impl<H: Host> Scan for Regions<H> {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        if let Some((scanner, end)) = &mut self.guest {
+            if cursor.pos() < *end {
+                return cursor.within(*end, |cursor| scanner.scan(cursor));
+            }
+            self.guest = None;
+        }
+        let token = self.host.scan(cursor)?;
+        if let Some((lang, end)) = self.host.region() {
+            self.guest = Some((lang.scanner(), end));
         }
+        Ok(token)
+    }
+}
 
-        // Check status of string
-        if self.in_string {
-            let current_char = self.buf.pop_front();
-            // Check if the next character needs to be escaped
-            if current_char == '\\' && !self.escape_next {
-                self.escape_next = true;
-                return TriOpt::Some(current_char);
-            }
-            // check if the string has ended (not an escaped delimiter)
-            if Some(current_char) == self.string_delimiter && !self.escape_next {
-                self.in_string = false;
-                self.string_delimiter = None;
-            }
-            // Reset the escape state
-            self.escape_next = false;
-            return TriOpt::Some(current_char);
+// Yields the characters of the code and string literals reported by a Lexer, dropping comments.
+// Lexing errors are passed through, after which the iterator ends.
+pub struct WithoutComments<'a> {
+    events: Lexer<'a>,
+    current: Chars<'a>,
+    keep_docs: bool, // Whether documentation comments are passed through untouched
+    keep_legal: bool, // Whether license notices are passed through untouched
+    keep_directives: bool, // Whether comments interpreted by tools are passed through untouched
+    pass_docstrings: bool, // Whether removed docstrings are replaced with `pass`
+}
+
+impl<'a> WithoutComments<'a> {
+    pub fn new(events: Lexer<'a>) -> Self {
+        Self {
+            events,
+            current: "".chars(),
+            keep_docs: false,
+            keep_legal: true,
+            keep_directives: true,
+            pass_docstrings: false,
         }
+    }
 
-        if let Some((idx, ref mut nesting)) = self.state {
-            let comment = &self.comments[idx];
-            let &Comment {
-                open_pat,
-                close_pat,
-                keep_close_pat,
-                ..
-            } = comment;
-
-            if self.buf.matches(close_pat) {
-                if !keep_close_pat {
-                    self.buf.pop_front_n(close_pat.len());
-                }
+    // Controls whether documentation comments are kept in the output.
+    pub fn keep_docs(mut self, keep: bool) -> Self {
+        self.keep_docs = keep;
+        self
+    }
 
-                match nesting {
-                    // non-nesting comment or top-level comment
-                    None | Some(0) => self.state = None,
-                    // nested comment
-                    Some(d) => *d -= 1,
-                }
-            } else if let Some(depth) = nesting {
-                if self.buf.matches(open_pat) {
-                    // matched nesting open pattern
-                    self.buf.pop_front_n(open_pat.len());
-                    *depth += 1;
-                } else {
-                    self.buf.pop_front();
-                }
-            } else {
-                self.buf.pop_front();
-            }
+    // Controls whether license notices (`/*! ... */`, `@license`, `@preserve`) are kept in the output.
+    pub fn keep_legal(mut self, keep: bool) -> Self {
+        self.keep_legal = keep;
+        self
+    }
 
-            TriOpt::Wait
-        } else {
-            for (idx, comment) in self.comments.iter().enumerate() {
-                let Comment {
-                    open_pat,
-                    close_pat,
-                    nests,
-                    allow_close_pat,
-                    ..
-                } = comment;
-
-                // if it matches open pattern, open
-                if self.buf.matches(open_pat) {
-                    self.buf.pop_front_n(open_pat.len());
-
-                    let nesting = match nests {
-                        true => Some(0),
-                        false => None,
-                    };
-                    self.state = Some((idx, nesting));
-                    return TriOpt::Wait;
-                } else if self.buf.matches(close_pat) && !*allow_close_pat {
-                    // if close pattern forbidden, panic
-                    panic!("Got \"{}\" without matching \"{}\"", close_pat, open_pat)
-                }
+    // Controls whether comments that tools interpret (`//go:build`...) are kept in the output.
+    pub fn keep_directives(mut self, keep: bool) -> Self {
+        self.keep_directives = keep;
+        self
+    }
 
-                // Enter the logic for handling string state
-                if let Some(&first_char) = self.buf.front() {
-                    match first_char {
-                        // Detects the beginning of a string
-                        '"' | '\'' => {
-                            self.in_string = true;
-                            self.string_delimiter = Some(first_char);
-                            return TriOpt::Some(self.buf.pop_front());
-                        }
-                        // Special handling of Python triple-quotes
-                        '`' if self.buf.matches("```") => {
-                            self.in_string = true;
-                            self.string_delimiter = Some('`');
-                            self.buf.pop_front_n(3);
-                            return TriOpt::Some('`');
-                        }
-                        _ => {}
-                    }
-                }
-            }
+    // Controls whether removed docstrings are replaced with `pass`, which keeps a body that held
+    // nothing but its docstring valid.
+    pub fn pass_docstrings(mut self, pass: bool) -> Self {
+        self.pass_docstrings = pass;
+        self
+    }
 
-            TriOpt::Some(self.buf.pop_front())
+    fn keeps(&self, kind: CommentKind) -> bool {
+        match kind {
+            CommentKind::Doc => self.keep_docs,
+            CommentKind::Legal => self.keep_legal,
+            CommentKind::Directive => self.keep_directives,
+            CommentKind::Line | CommentKind::Block => false,
         }
     }
 }
 
-impl<I: Iterator<Item = char>> Iterator for WithoutComments<I> {
-    type Item = char;
+impl<'a> Iterator for WithoutComments<'a> {
+    type Item = Result<char, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.next_() {
-                TriOpt::None => return None,
-                TriOpt::Some(c) => return Some(c),
-                TriOpt::Wait => (),
+            if let Some(c) = self.current.next() {
+                return Some(Ok(c));
             }
+            let span = match self.events.next()? {
+                Ok(Event::Code(span) | Event::StringLiteral(span)) => span,
+                Ok(Event::Comment { kind, span, .. }) if self.keeps(kind) => span,
+                // docstrings are the documentation comments delimited by quotes
+                Ok(Event::Comment { kind: CommentKind::Doc, delimiter, .. })
+                    if self.pass_docstrings && delimiter.ends_with(['"', '\'']) =>
+                {
+                    self.current = "pass".chars();
+                    continue;
+                }
+                Ok(Event::Comment { .. }) => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            self.current = self.events.source()[span.start..span.end].chars();
         }
     }
 }
 
-pub trait IntoWithoutComments
-    where
-        Self: Sized + Iterator<Item = char>,
-{
-    fn purge_commentaries(self, language: Box<[Comment]>) -> WithoutComments<Self> {
-        let mut buf_len = 0; // Initialize the buffer length to zero.
-        for &Comment { open_pat, close_pat, .. } in language.iter() // Iterate over the language-specific comment patterns.
-        {
-            // Find the length of the longest opening or closing pattern.
-            if open_pat.len() > buf_len {
-                buf_len = open_pat.len() // Update buffer length to the length of the opening pattern if it's longer.
-            }
-            if close_pat.len() > buf_len {
-                buf_len = close_pat.len() // Update buffer length to the length of the closing pattern if it's longer.
-            }
-        }
-        assert_ne!(buf_len, 0); // Ensure that the buffer length is not zero, i.e., there are comment patterns.
-        WithoutComments::new(self, language, buf_len) // Create a new WithoutComments iterator with the computed buffer length.
-    }
+pub trait IntoWithoutComments {
+    fn purge_commentaries(&self, language: Box<[Comment]>) -> WithoutComments<'_>;
 }
 
-
-impl<I: Iterator<Item = char>> IntoWithoutComments for I {}
-
-pub fn proc_trimming(path_buf: &str, lang: Type) -> Result<String, String> {
-    let mut file = File::open(path_buf).map_err(|_| "File does not exist".to_string())?;
-    let mut file_contents = String::new();
-    file.read_to_string(&mut file_contents).map_err(|_| "Failed to read file".to_string())?;
-
-    let lang_config = match lang {
-        Type::RustC => RUSTC.to_vec().into_boxed_slice(),
-        Type::Python => PYTHON.to_vec().into_boxed_slice(),
-        Type::Haskell => HASKELL.to_vec().into_boxed_slice(),
-        Type::Markup => MARKUP.to_vec().into_boxed_slice(),
-    };
-
-    // Assuming `without_comments` is a method provided elsewhere.
-    Ok(file_contents.chars().purge_commentaries(lang_config).collect())
-}
\ No newline at end of file
+impl IntoWithoutComments for str {
+    fn purge_commentaries(&self, language: Box<[Comment]>) -> WithoutComments<'_> {
+        WithoutComments::new(Lexer::new(self, language))
+    }
+}
@@ -0,0 +1,185 @@
+use super::shell::{heredoc, heredoc_bodies, TABS};
+use crate::lexer::{expand, CommentKind, Cursor, Fault, Scan, Token};
+
+// Finishes a CMake bracket comment or argument once its opening bracket, which captured `level`,
+// has been consumed.
+fn close_bracket(cursor: &mut Cursor<'_>, level: &str, start: usize, comment: bool) -> Result<Token, Fault> {
+    let close = expand("]{=}]", level);
+    let open = cursor.pos() - start;
+    if cursor.skip_until(&close) {
+        cursor.skip(close.len());
+        return Ok(match comment {
+            true => Token::Comment { kind: CommentKind::Block, open, close: close.len() },
+            false => Token::Str,
+        });
+    }
+    match (cursor.recovering(), comment) {
+        (true, true) => Ok(Token::Comment { kind: CommentKind::Block, open, close: 0 }),
+        (true, false) => Ok(Token::Str),
+        (false, true) => Err(Fault::UnterminatedComment { open: expand("#[{=}[", level), at: start }),
+        (false, false) => Err(Fault::UnterminatedString { at: start }),
+    }
+}
+
+// Scanner for CMake: `#` comments, `#[[ ]]` bracket comments, bracket arguments and quoted arguments.
+pub(crate) struct CMake;
+
+impl Scan for CMake {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        if cursor.eat("#") {
+            if let Some(level) = cursor.eat_delimiter("[{=}[") {
+                return close_bracket(cursor, level, start, true);
+            }
+            return Ok(cursor.line_comment(CommentKind::Line, 1));
+        }
+        if let Some(level) = cursor.eat_delimiter("[{=}[") {
+            return close_bracket(cursor, level, start, false);
+        }
+        match cursor.bump() {
+            Some('"') => cursor.quoted("\"", Some('\\'), start),
+            Some('\\') => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+// Scanner for makefiles: `#` starts a comment that a trailing backslash continues, except in recipe
+// lines, which belong to the shell and where quotes protect it.
+pub(crate) struct Make {
+    recipe: bool, // whether the current line is a recipe line
+}
+
+impl Make {
+    pub(crate) fn new() -> Self {
+        Self { recipe: false }
+    }
+}
+
+impl Scan for Make {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        if cursor.at_line_begin() {
+            self.recipe = cursor.starts_with("\t");
+        }
+
+        if self.recipe {
+            let word_start = cursor.before().ends_with(|c: char| c.is_whitespace() || c == ';');
+            return match cursor.bump() {
+                Some('#') if word_start => Ok(cursor.line_comment(CommentKind::Line, 1)),
+                Some('\'') => cursor.quoted("'", None, start),
+                Some('"') => cursor.quoted("\"", Some('\\'), start),
+                Some('\\') => {
+                    cursor.bump();
+                    Ok(Token::Code)
+                }
+                _ => Ok(Token::Code),
+            };
+        }
+
+        match cursor.bump() {
+            Some('#') => {
+                while cursor.skip_until("\n") {
+                    if !cursor.before().trim_end_matches('\r').ends_with('\\') {
+                        break;
+                    }
+                    cursor.bump();
+                }
+                Ok(Token::Comment { kind: CommentKind::Line, open: 1, close: 0 })
+            }
+            Some('\\') => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+// Parser directives that may open a Dockerfile, such as `# syntax=docker/dockerfile:1`.
+const DIRECTIVES: [&str; 3] = ["syntax", "escape", "check"];
+
+// Scanner for Dockerfiles: comments are whole lines starting with `#`, parser directives at the top
+// are kept, and here-documents (`RUN <<EOF`) are left alone.
+pub(crate) struct Dockerfile {
+    directives: bool,                         // whether parser directives may still appear
+    heredocs: Vec<(String, &'static [char])>, // here-documents opened on the current line
+}
+
+impl Dockerfile {
+    pub(crate) fn new() -> Self {
+        Self { directives: true, heredocs: Vec::new() }
+    }
+}
+
+impl Scan for Dockerfile {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if cursor.at_line_begin() && !self.heredocs.is_empty() {
+            return heredoc_bodies(cursor, &mut self.heredocs, start);
+        }
+        if cursor.at_line_start() && cursor.starts_with("#") {
+            let line = cursor.rest().split('\n').next().unwrap_or_default();
+            let directive = line[1..].split_once('=').is_some_and(|(key, _)| {
+                DIRECTIVES.iter().any(|d| key.trim().eq_ignore_ascii_case(d))
+            });
+            let kind = match self.directives && directive {
+                true => CommentKind::Directive,
+                false => CommentKind::Line,
+            };
+            self.directives &= directive;
+            cursor.skip(1);
+            return Ok(cursor.line_comment(kind, 1));
+        }
+        match cursor.peek() {
+            Some('\n') if cursor.at_line_begin() => self.directives = false,
+            Some(c) if !c.is_whitespace() => self.directives = false,
+            _ => {}
+        }
+
+        if cursor.starts_with("<<") {
+            if let Some((word, strip_tabs)) = heredoc(cursor) {
+                self.heredocs.push((word, if strip_tabs { TABS } else { &[] }));
+                return Ok(Token::Code);
+            }
+        }
+        cursor.bump();
+        Ok(Token::Code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(lang: Type, src: &str) -> String {
+        Stripper::new(lang).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn makefiles() {
+        let src = "A = 1 # c \\\n  still c\nall:\n\techo \"#\" '#' a#b # d\n";
+        assert_eq!(strip(Type::Make, src), "A = 1 \nall:\n\techo \"#\" '#' a#b \n");
+        assert_eq!(strip(Type::Make, "B = \\# # c\n"), "B = \\# \n");
+    }
+
+    #[test]
+    fn dockerfiles() {
+        let src = "# syntax=docker/dockerfile:1\n# c\nFROM a # not a comment\nRUN <<EOF\n# kept\nEOF\n  # d\n";
+        assert_eq!(strip(Type::Dockerfile, src), "# syntax=docker/dockerfile:1\n\nFROM a # not a comment\nRUN <<EOF\n# kept\nEOF\n  \n");
+        // parser directives are only recognised before anything else
+        assert_eq!(strip(Type::Dockerfile, "FROM a\n# escape=`\n"), "FROM a\n\n");
+    }
+
+    #[test]
+    fn cmake() {
+        let src = "set(A \"#\" [=[ # ]=]) # c\n#[[ d\n]] message(x)\n#[=[ e ]] ]=]\n";
+        assert_eq!(strip(Type::CMake, src), "set(A \"#\" [=[ # ]=]) \n message(x)\n\n");
+        assert!(Stripper::new(Type::CMake).strip_str("#[[ open").is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Scanner for C and C++: string prefixes, digit separators, line splices in `//` comments and
+// preprocessor lines, plus raw strings in C++.
+pub(crate) struct C {
+    cpp: bool,
+    header_name: bool, // next `<` starts the header name of an `#include`
+    prose: bool,       // the rest of the line is the free text of an `#error` or `#warning`
+}
+
+impl C {
+    pub(crate) fn new(cpp: bool) -> Self {
+        Self { cpp, header_name: false, prose: false }
+    }
+
+    // Finishes a `//` comment, which a trailing backslash continues onto the next line.
+    fn line_comment(cursor: &mut Cursor<'_>, kind: CommentKind, open: usize) -> Token {
+        while cursor.skip_until("\n") {
+            if !cursor.before().trim_end_matches('\r').ends_with('\\') {
+                break;
+            }
+            cursor.bump();
+        }
+        Token::Comment { kind, open, close: 0 }
+    }
+
+    // Finishes a C++ raw string such as `R"x(...)x"` once its prefix and quote have been consumed.
+    fn raw_string(cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+        let delim = cursor.eat_while(|c| c != '(' && c != '"' && c != '\\' && !c.is_whitespace() && c != ')');
+        if !cursor.eat("(") {
+            // not a valid raw string delimiter: lex the rest as an ordinary string
+            return cursor.quoted("\"", Some('\\'), start);
+        }
+        let close = format!("){}\"", delim);
+        cursor.quoted(&close, None, start)
+    }
+}
+
+impl Scan for C {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if cursor.eat("//") {
+            // Doxygen `///` and `//!`
+            let doc = cursor.starts_with("!") || (cursor.starts_with("/") && !cursor.starts_with("//"));
+            return Ok(match doc {
+                true => Self::line_comment(cursor, CommentKind::Doc, 3),
+                false => Self::line_comment(cursor, CommentKind::Line, 2),
+            });
+        }
+        if cursor.eat("/*") {
+            // Doxygen `/**` and `/*!`
+            let kind = match cursor.eat("!") || cursor.eat_doc_star() {
+                true => CommentKind::Doc,
+                false => CommentKind::Block,
+            };
+            return cursor.block_comment(kind, "/*", "*/", false, start);
+        }
+        if let Some(token) = cursor.unmatched_close("/*", "*/") {
+            return token;
+        }
+
+        let c = match cursor.peek() {
+            Some(c) if c.is_whitespace() => {
+                if c == '\n' && !cursor.before().ends_with('\\') {
+                    self.prose = false;
+                }
+                cursor.bump();
+                return Ok(Token::Code);
+            }
+            Some(c) => c,
+            None => return Ok(Token::Code),
+        };
+        let header_name = std::mem::take(&mut self.header_name);
+
+        match c {
+            '#' if cursor.at_line_start() => {
+                cursor.bump();
+                cursor.eat_while(|c| c == ' ' || c == '\t');
+                match cursor.eat_while(is_ident_continue) {
+                    "include" | "include_next" | "import" => self.header_name = true,
+                    "error" | "warning" => self.prose = true,
+                    _ => {}
+                }
+                Ok(Token::Code)
+            }
+            '<' if header_name => {
+                cursor.bump();
+                cursor.quoted(">", None, start)
+            }
+            c if is_ident_start(c) => {
+                let word = cursor.eat_while(is_ident_continue);
+                match (word, cursor.peek()) {
+                    ("R" | "u8R" | "uR" | "UR" | "LR", Some('"')) if self.cpp => {
+                        cursor.bump();
+                        Self::raw_string(cursor, start)
+                    }
+                    ("L" | "u" | "U" | "u8", Some(quote @ ('"' | '\''))) if !self.prose => {
+                        cursor.bump();
+                        cursor.quoted(if quote == '"' { "\"" } else { "'" }, Some('\\'), start)
+                    }
+                    _ => Ok(Token::Code),
+                }
+            }
+            // Numbers, where `'` may separate digits as in `1'000'000`
+            c if c.is_ascii_digit() || (c == '.' && cursor.peek_nth(1).is_some_and(|c| c.is_ascii_digit())) => {
+                cursor.bump();
+                loop {
+                    match (cursor.peek(), cursor.peek_nth(1)) {
+                        (Some('e' | 'E' | 'p' | 'P'), Some('+' | '-')) => cursor.skip(2),
+                        (Some('\''), Some(c)) if c.is_ascii_alphanumeric() => cursor.skip(1),
+                        (Some(c), _) if c.is_ascii_alphanumeric() || c == '.' || c == '_' => cursor.skip(1),
+                        _ => break,
+                    }
+                }
+                Ok(Token::Code)
+            }
+            '"' | '\'' if !self.prose => {
+                cursor.bump();
+                cursor.quoted(if c == '"' { "\"" } else { "'" }, Some('\\'), start)
+            }
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+
+    fn strip(src: &str, lang: Type) -> String {
+        crate::strip_str(src, lang).unwrap()
+    }
+
+    #[test]
+    fn raw_strings() {
+        assert_eq!(strip("auto s = R\"d( */ )\" )d\"; // c\n", Type::Cpp), "auto s = R\"d( */ )\" )d\"; \n");
+        assert_eq!(strip("auto s = u8R\"(// a)\"; /* c */\n", Type::Cpp), "auto s = u8R\"(// a)\"; \n");
+    }
+
+    #[test]
+    fn digit_separators() {
+        assert_eq!(strip("int x = 1'000'000; // it's\nchar c = '\"'; // c\n", Type::Cpp), "int x = 1'000'000; \nchar c = '\"'; \n");
+    }
+
+    #[test]
+    fn continued_line_comments() {
+        assert_eq!(strip("a; // b \\\n c\nd;\n", Type::C), "a; \nd;\n");
+        assert_eq!(strip("a; // b \\\r\n c\nd;\n", Type::C), "a; \nd;\n");
+        assert_eq!(strip("a; // b \\ c\nd;\n", Type::C), "a; \nd;\n");
+    }
+
+    #[test]
+    fn header_names() {
+        assert_eq!(strip("#include <a/*b>\nint x; /* c */\n", Type::C), "#include <a/*b>\nint x; \n");
+        assert_eq!(strip("# include \"a//b.h\" // c\n", Type::Cpp), "# include \"a//b.h\" \n");
+        assert_eq!(strip("#pragma once // it's a header\n", Type::C), "#pragma once \n");
+    }
+}
@@ -0,0 +1,231 @@
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Comment prefixes that tools act upon, such as `# yaml-language-server: $schema=...`.
+const DIRECTIVES: [&str; 2] = ["yaml-language-server:", "yamllint "];
+
+// Whether a `#` at the cursor starts a comment, which needs a blank or a line start before it.
+fn after_blank(cursor: &Cursor<'_>) -> bool {
+    cursor.before().chars().next_back().is_none_or(char::is_whitespace)
+}
+
+// Whether a YAML node may start at the cursor: at the start of a line, or after an indicator such as
+// `key:`, `- ` or `[`, maybe followed by a tag or an anchor.
+fn node_start(cursor: &Cursor<'_>) -> bool {
+    let before = cursor.before();
+    let line = before[before.rfind('\n').map_or(0, |i| i + 1)..].trim_end();
+    let last_word = line.rsplit(char::is_whitespace).next().unwrap_or_default();
+    line.is_empty() || line.ends_with([':', '-', '?', '[', '{', ',']) || last_word.starts_with(['!', '&'])
+}
+
+// Width of the leading blanks of `line`.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+// A block scalar opened by `|` or `>`, whose content starts on the next line.
+struct BlockScalar {
+    parent: usize,         // indentation of the line that opened it
+    indent: Option<usize>, // indentation of its content, if an indicator gives it
+}
+
+// Scanner for YAML: `#` only starts a comment after a blank, and quoted and block scalars are strings.
+pub(crate) struct Yaml {
+    block: Option<BlockScalar>, // block scalar opened on the current line
+}
+
+impl Yaml {
+    pub(crate) fn new() -> Self {
+        Self { block: None }
+    }
+
+    // Records the block scalar opened at the cursor by `|` or `>`, followed by its indicators and
+    // maybe a comment, if there is one.
+    fn block_scalar(&mut self, cursor: &mut Cursor<'_>) -> bool {
+        let line = cursor.rest().split('\n').next().unwrap_or_default().trim_end_matches('\r');
+        let header = line[1..].trim_start_matches(|c: char| c.is_ascii_digit() || c == '+' || c == '-');
+        let indicators = &line[1..line.len() - header.len()];
+        let after = header.trim_start_matches([' ', '\t']);
+        let comment = after.starts_with('#') && after.len() < header.len();
+        if !after.is_empty() && !comment {
+            return false;
+        }
+        let before = cursor.before();
+        let parent = indentation(&before[before.rfind('\n').map_or(0, |i| i + 1)..]);
+        // an explicit indentation lets the content start with more blanks
+        let indent = indicators.contains(|c: char| c.is_ascii_digit()).then_some(parent + 1);
+        self.block = Some(BlockScalar { parent, indent });
+        cursor.skip(1 + indicators.len());
+        true
+    }
+
+    // Consumes the content of a block scalar, which starts at the cursor: the lines indented deeper
+    // than its parent, and the blank ones between them.
+    fn block_content(cursor: &mut Cursor<'_>, block: BlockScalar) -> Token {
+        let mut indent = block.indent;
+        let mut end = cursor.pos();
+        for line in cursor.rest().split_inclusive('\n') {
+            let blank = line.trim().is_empty();
+            let depth = indentation(line);
+            if !blank {
+                let indent = *indent.get_or_insert(depth);
+                if depth <= block.parent || depth < indent {
+                    break;
+                }
+            }
+            end += line.len();
+        }
+        // blank lines after the content belong to what follows
+        let content = cursor.rest()[..end - cursor.pos()].trim_end();
+        cursor.skip(content.len());
+        match content.is_empty() {
+            true => Token::Code,
+            false => Token::Str,
+        }
+    }
+}
+
+impl Scan for Yaml {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if cursor.at_line_begin() {
+            if let Some(block) = self.block.take() {
+                return Ok(Self::block_content(cursor, block));
+            }
+        }
+        if cursor.starts_with("#") && after_blank(cursor) {
+            cursor.skip(1);
+            let kind = match DIRECTIVES.iter().any(|d| cursor.rest().trim_start_matches(' ').starts_with(d)) {
+                true => CommentKind::Directive,
+                false => CommentKind::Line,
+            };
+            return Ok(cursor.line_comment(kind, 1));
+        }
+
+        // quotes and block indicators only count at the start of a node
+        let node_start = node_start(cursor);
+        match cursor.peek() {
+            Some('"') if node_start => {
+                cursor.bump();
+                cursor.quoted("\"", Some('\\'), start)
+            }
+            Some('\'') if node_start => {
+                cursor.bump();
+                let mut token = cursor.quoted("'", None, start);
+                // a doubled quote stands for one
+                while token.is_ok() && cursor.eat("'") {
+                    token = cursor.quoted("'", None, start);
+                }
+                token
+            }
+            Some('|' | '>') if node_start && self.block_scalar(cursor) => Ok(Token::Code),
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+// Scanner for INI files: `;` and `#` start comments at the beginning of a line only, since values may
+// hold them.
+pub(crate) struct Ini;
+
+impl Scan for Ini {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        if cursor.at_line_start() && (cursor.starts_with(";") || cursor.starts_with("#")) {
+            cursor.skip(1);
+            return Ok(cursor.line_comment(CommentKind::Line, 1));
+        }
+        cursor.bump();
+        Ok(Token::Code)
+    }
+}
+
+// Scanner for Java properties: `#` and `!` start comments at the beginning of a line, unless a trailing
+// backslash continued the previous line into this one.
+pub(crate) struct Properties {
+    continued: bool, // whether the current line continues the previous one
+}
+
+impl Properties {
+    pub(crate) fn new() -> Self {
+        Self { continued: false }
+    }
+}
+
+impl Scan for Properties {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        if cursor.at_line_start() && !self.continued && (cursor.starts_with("#") || cursor.starts_with("!")) {
+            cursor.skip(1);
+            return Ok(cursor.line_comment(CommentKind::Line, 1));
+        }
+        match cursor.bump() {
+            Some('\\') => {
+                self.continued = cursor.eat("\n") || cursor.eat("\r\n");
+                if !self.continued {
+                    cursor.bump();
+                }
+            }
+            Some('\n') => self.continued = false,
+            _ => {}
+        }
+        Ok(Token::Code)
+    }
+}
+
+// Scanner for `.env` files: `#` starts a comment at the beginning of a line or after a blank, and
+// values may be quoted over several lines.
+pub(crate) struct Dotenv;
+
+impl Scan for Dotenv {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        if cursor.starts_with("#") && after_blank(cursor) {
+            cursor.skip(1);
+            return Ok(cursor.line_comment(CommentKind::Line, 1));
+        }
+        // quotes only count at the start of a value
+        let value_start = cursor.before().ends_with('=');
+        match cursor.bump() {
+            Some('"') if value_start => cursor.quoted("\"", Some('\\'), start),
+            Some('\'') if value_start => cursor.quoted("'", None, start),
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(lang: Type, src: &str) -> String {
+        Stripper::new(lang).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn yaml() {
+        let src = "# yaml-language-server: $schema=a.json\na: b#c # d\ne: \"# f\" # g\nh: '#''#'\n";
+        assert_eq!(strip(Type::Yaml, src), "# yaml-language-server: $schema=a.json\na: b#c \ne: \"# f\" \nh: '#''#'\n");
+    }
+
+    #[test]
+    fn yaml_block_scalars() {
+        let src = "a: | # c\n  # kept\n\n  x\nb: >2\n   # kept\n# d\n";
+        assert_eq!(strip(Type::Yaml, src), "a: | \n  # kept\n\n  x\nb: >2\n   # kept\n\n");
+    }
+
+    #[test]
+    fn toml() {
+        let src = "a = \"#\" # c\nb = '''\n# kept'''\n";
+        assert_eq!(strip(Type::Toml, src), "a = \"#\" \nb = '''\n# kept'''\n");
+    }
+
+    #[test]
+    fn line_start_comments() {
+        assert_eq!(strip(Type::Ini, "; c\n[a]\nb = c ; d\n  # e\n"), "\n[a]\nb = c ; d\n  \n");
+        assert_eq!(strip(Type::Properties, "# c\na = b \\\n  # kept\n! d\n"), "\na = b \\\n  # kept\n\n");
+        assert_eq!(strip(Type::Dotenv, "# c\nA=b#c # d\nB=\"x\n# kept\"\n"), "\nA=b#c \nB=\"x\n# kept\"\n");
+    }
+}
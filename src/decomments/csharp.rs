@@ -0,0 +1,235 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Shape of a C# string literal.
+#[derive(Copy, Clone)]
+struct Literal {
+    verbatim: bool, // `@"..."`: backslashes are literal and `""` is a quote
+    quotes: usize,  // number of quotes delimiting a raw string, 0 otherwise
+    dollars: usize, // number of `$` of an interpolated string, 0 otherwise
+}
+
+// An interpolation hole being lexed as code.
+struct Hole {
+    literal: Literal,
+    depth: usize, // brackets opened inside the hole
+}
+
+// Scanner for C#: `///` XML documentation, verbatim, interpolated and raw string literals.
+pub(crate) struct CSharp {
+    holes: Vec<Hole>,
+    prose: bool, // the rest of the line is the free text of a `#region` or similar directive
+}
+
+// Consumes the braces that close an interpolation hole: one per `$` of the string, or fewer where the
+// input is malformed.
+fn close_hole(cursor: &mut Cursor<'_>, literal: Literal) {
+    for _ in 0..literal.dollars.max(1) {
+        if !cursor.eat("}") {
+            break;
+        }
+    }
+}
+
+impl CSharp {
+    pub(crate) fn new() -> Self {
+        Self { holes: Vec::new(), prose: false }
+    }
+
+    // Lexes the body of a string literal up to its end or to the next interpolation hole.
+    fn body(&mut self, cursor: &mut Cursor<'_>, literal: Literal, start: usize) -> Result<Token, Fault> {
+        let Literal { verbatim, quotes, dollars } = literal;
+        loop {
+            if quotes > 0 {
+                if cursor.rest().bytes().take_while(|&b| b == b'"').count() >= quotes {
+                    cursor.skip(quotes);
+                    return Ok(Token::Str);
+                }
+            } else if verbatim && cursor.eat("\"\"") {
+                continue;
+            } else if cursor.eat("\"") {
+                return Ok(Token::Str);
+            }
+
+            if dollars > 0 && cursor.starts_with("{") {
+                let braces = cursor.rest().bytes().take_while(|&b| b == b'{').count();
+                if quotes == 0 && braces >= 2 {
+                    // `{{` is an escaped brace
+                    cursor.skip(2);
+                    continue;
+                }
+                if braces >= dollars {
+                    cursor.skip(braces);
+                    self.holes.push(Hole { literal, depth: 0 });
+                    return Ok(Token::Str);
+                }
+                cursor.skip(braces);
+                continue;
+            }
+
+            match cursor.bump() {
+                Some('\\') if !verbatim && quotes == 0 => {
+                    cursor.bump();
+                }
+                Some(_) => {}
+                None if cursor.recover => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+}
+
+impl Scan for CSharp {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if cursor.eat("//") {
+            // `///` introduces XML documentation, `////` does not
+            return Ok(match cursor.starts_with("/") && !cursor.starts_with("//") {
+                true => cursor.line_comment(CommentKind::Doc, 3),
+                false => cursor.line_comment(CommentKind::Line, 2),
+            });
+        }
+        if cursor.eat("/*") {
+            let kind = match cursor.eat_doc_star() {
+                true => CommentKind::Doc,
+                false => CommentKind::Block,
+            };
+            return cursor.block_comment(kind, "/*", "*/", false, start);
+        }
+        if let Some(token) = cursor.unmatched_close("/*", "*/") {
+            return token;
+        }
+
+        let Some(c) = cursor.peek() else { return Ok(Token::Code) };
+
+        if let Some(hole) = self.holes.last_mut() {
+            match c {
+                '{' | '(' | '[' => hole.depth += 1,
+                ')' | ']' => hole.depth = hole.depth.saturating_sub(1),
+                '}' if hole.depth > 0 => hole.depth -= 1,
+                '}' => {
+                    // end of the hole: the string resumes
+                    let literal = self.holes.pop().unwrap().literal;
+                    close_hole(cursor, literal);
+                    return self.body(cursor, literal, start);
+                }
+                ':' if hole.depth == 0 && cursor.peek_nth(1) != Some(':') => {
+                    // format specifier, which runs to the end of the hole
+                    let literal = self.holes.pop().unwrap().literal;
+                    cursor.skip_until("}");
+                    close_hole(cursor, literal);
+                    return self.body(cursor, literal, start);
+                }
+                _ => {}
+            }
+        }
+
+        match c {
+            '\n' => {
+                self.prose = false;
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            '#' if cursor.at_line_start() => {
+                cursor.bump();
+                cursor.eat_while(|c| c == ' ' || c == '\t');
+                if let "region" | "endregion" | "error" | "warning" = cursor.eat_while(is_ident_continue) {
+                    self.prose = true;
+                }
+                Ok(Token::Code)
+            }
+            _ if self.prose => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            '$' | '@' | '"' => {
+                let dollars = cursor.eat_while(|c| c == '$').len();
+                let verbatim = cursor.eat("@");
+                let dollars = dollars + cursor.eat_while(|c| c == '$').len();
+                let quotes = cursor.rest().bytes().take_while(|&b| b == b'"').count();
+                match quotes {
+                    0 => {
+                        // `@ident` or a stray `$`
+                        cursor.eat_while(is_ident_continue);
+                        Ok(Token::Code)
+                    }
+                    _ if verbatim || quotes < 3 => {
+                        cursor.skip(1);
+                        self.body(cursor, Literal { verbatim, quotes: 0, dollars }, start)
+                    }
+                    _ => {
+                        cursor.skip(quotes);
+                        self.body(cursor, Literal { verbatim: false, quotes, dollars }, start)
+                    }
+                }
+            }
+            '\'' => {
+                cursor.bump();
+                cursor.quoted("'", Some('\\'), start)
+            }
+            c if is_ident_start(c) => {
+                cursor.eat_while(is_ident_continue);
+                Ok(Token::Code)
+            }
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str) -> String {
+        Stripper::new(Type::CSharp).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn verbatim_strings() {
+        assert_eq!(strip("var p = @\"C:\\dir\\\"; // c\n"), "var p = @\"C:\\dir\\\"; \n");
+        assert_eq!(strip("var q = @\"say \"\"// hi\"\"\"; /* c */\n"), "var q = @\"say \"\"// hi\"\"\"; \n");
+    }
+
+    #[test]
+    fn raw_strings() {
+        assert_eq!(strip("var r = \"\"\"a \"\" // b\"\"\"; // c\n"), "var r = \"\"\"a \"\" // b\"\"\"; \n");
+        assert_eq!(strip("var r = \"\"\"\"\n  \"\"\" /* \n  \"\"\"\"; // c\n"), "var r = \"\"\"\"\n  \"\"\" /* \n  \"\"\"\"; \n");
+    }
+
+    #[test]
+    fn interpolation() {
+        let src = "var s = $$\"\"\"{{x /* c */}} // \"\"\"; // c\n";
+        assert_eq!(strip(src), "var s = $$\"\"\"{{x }} // \"\"\"; \n");
+        assert_eq!(strip("var t = $\"{a:N2} // {b}\"; // c"), "var t = $\"{a:N2} // {b}\"; ");
+        assert_eq!(strip("var u = $\"{{ // }} {f(\"x\") /* c */}\";\n"), "var u = $\"{{ // }} {f(\"x\") }\";\n");
+    }
+
+    #[test]
+    fn documentation() {
+        let src = "/// <summary>Doc.</summary>\n//// c\nclass A {}\n";
+        assert_eq!(strip(src), "\n\nclass A {}\n");
+        assert_eq!(Stripper::new(Type::CSharp).keep_docs(true).strip_str(src).unwrap(), "/// <summary>Doc.</summary>\n\nclass A {}\n");
+    }
+
+    #[test]
+    fn region_names() {
+        assert_eq!(strip("#region Don't panic // c\nint x; // c\n#endregion\n"), "#region Don't panic \nint x; \n#endregion\n");
+    }
+
+    #[test]
+    fn pragmas() {
+        assert_eq!(strip("#pragma warning disable CS0168 // it's unused\nint x; // c\n"), "#pragma warning disable CS0168 \nint x; \n");
+    }
+
+    #[test]
+    fn malformed_raw_interpolation() {
+        let csharp = Stripper::new(Type::CSharp).recover(true);
+        assert_eq!(csharp.strip_str("var s = $$\"\"\"{{x}").unwrap(), "var s = $$\"\"\"{{x}");
+        assert_eq!(csharp.strip_str("var s = $$\"\"\"{{x}é\"\"\"; // c").unwrap(), "var s = $$\"\"\"{{x}é\"\"\"; ");
+    }
+}
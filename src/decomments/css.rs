@@ -0,0 +1,159 @@
+use super::rust::is_ident_continue;
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Stylesheet languages handled by the Css scanner.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    Css,
+    Scss,
+    Less,
+    Sass, // the indented syntax
+}
+
+// Classifies a comment by its body: loud `/*! ... */` comments survive minifiers, so they are license
+// notices, and source map references are directives.
+fn classify(text: &str, kind: CommentKind) -> CommentKind {
+    let source_map = text.strip_prefix(['#', '@']).is_some_and(|text| text.trim_start().starts_with("sourceMappingURL="));
+    if text.starts_with('!') {
+        CommentKind::Legal
+    } else if source_map {
+        CommentKind::Directive
+    } else {
+        kind
+    }
+}
+
+// Finishes a comment of the indented syntax, which also takes in the following lines that are
+// indented deeper than the line it starts, `indent`.
+fn indented(cursor: &mut Cursor<'_>, indent: usize) {
+    cursor.skip_until("\n");
+    while let Some(next) = cursor.rest().strip_prefix('\n') {
+        let line = next.split('\n').next().unwrap_or_default();
+        let body = line.trim_start_matches([' ', '\t']);
+        if body.trim_end().is_empty() || line.len() - body.len() <= indent {
+            break;
+        }
+        cursor.skip(1 + line.len());
+    }
+}
+
+// Scanner for CSS, SCSS, Less and Sass: `url(...)` may hold an unquoted `//`, and the preprocessors add
+// `//` line comments. In the indented syntax of Sass, comments run over the lines indented below them.
+pub(crate) struct Css {
+    flavor: Flavor,
+}
+
+impl Css {
+    pub(crate) fn new(flavor: Flavor) -> Self {
+        Self { flavor }
+    }
+
+    // Indentation of the line at the cursor, if only blanks precede the cursor on it.
+    fn indent(&self, cursor: &Cursor<'_>) -> Option<usize> {
+        let before = cursor.before();
+        let line = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+        (self.flavor == Flavor::Sass && cursor.at_line_start()).then_some(line.len())
+    }
+}
+
+impl Scan for Css {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if self.flavor != Flavor::Css && cursor.starts_with("//") {
+            let indent = self.indent(cursor);
+            // SassDoc comments
+            let (open, kind) = match cursor.starts_with("///") {
+                true => (3, CommentKind::Doc),
+                false => (2, CommentKind::Line),
+            };
+            cursor.skip(open);
+            match indent {
+                Some(indent) => indented(cursor, indent),
+                None => {
+                    cursor.skip_until("\n");
+                }
+            }
+            return Ok(Token::Comment { kind: classify(&cursor.before()[start + open..], kind), open, close: 0 });
+        }
+        if cursor.starts_with("/*") {
+            // a comment of the indented syntax may be left open
+            let line = cursor.rest().split('\n').next().unwrap_or_default();
+            let indent = self.indent(cursor).filter(|_| !line.contains("*/"));
+            cursor.skip(2);
+            if let Some(indent) = indent {
+                indented(cursor, indent);
+                let kind = classify(&cursor.before()[start + 2..], CommentKind::Block);
+                return Ok(Token::Comment { kind, open: 2, close: 0 });
+            }
+            return match cursor.block_comment(CommentKind::Block, "/*", "*/", false, start)? {
+                Token::Comment { kind, open, close } => {
+                    let kind = classify(&cursor.before()[start + open..cursor.pos() - close], kind);
+                    Ok(Token::Comment { kind, open, close })
+                }
+                token => Ok(token),
+            };
+        }
+
+        match cursor.bump() {
+            Some(quote @ ('"' | '\'')) => cursor.quoted(quote.encode_utf8(&mut [0; 4]), Some('\\'), start),
+            Some('\\') => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            // an unquoted URL, such as `url(http://example.com)`, runs to the closing parenthesis
+            Some(c) if is_ident_continue(c) || c == '-' => {
+                cursor.eat_while(|c| is_ident_continue(c) || c == '-');
+                let word = &cursor.before()[start..];
+                let url = word.eq_ignore_ascii_case("url") && cursor.eat("(");
+                let quoted = cursor.rest().trim_start().starts_with(['"', '\'']);
+                if url && !quoted {
+                    let start = cursor.pos();
+                    if cursor.skip_until(")") {
+                        return Ok(Token::Str);
+                    }
+                    if !cursor.recovering() {
+                        return Err(Fault::UnterminatedString { at: start });
+                    }
+                    return Ok(Token::Str);
+                }
+                Ok(Token::Code)
+            }
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(lang: Type, src: &str) -> String {
+        Stripper::new(lang).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn urls() {
+        let src = "a { background: url(http://x.com/a.png) } /* c */";
+        assert_eq!(strip(Type::Css, src), "a { background: url(http://x.com/a.png) } ");
+        assert_eq!(strip(Type::Scss, "b { c: url(\"//x\"); } // d\n"), "b { c: url(\"//x\"); } \n");
+        // plain CSS has no line comments
+        assert_eq!(strip(Type::Css, "a { b: 'x' } // c"), "a { b: 'x' } // c");
+    }
+
+    #[test]
+    fn loud_comments_and_source_maps() {
+        let src = "/*! MIT */\n/* c */\n/*# sourceMappingURL=a.css.map */";
+        assert_eq!(strip(Type::Css, src), "/*! MIT */\n\n/*# sourceMappingURL=a.css.map */");
+        let scss = "/// Doc.\n$a: 1; // c\n";
+        assert_eq!(strip(Type::Scss, scss), "\n$a: 1; \n");
+        assert_eq!(Stripper::new(Type::Less).keep_docs(true).strip_str(scss).unwrap(), "/// Doc.\n$a: 1; \n");
+    }
+
+    #[test]
+    fn indented_syntax() {
+        let src = "// c\n  still c\na\n  b: 1\n  /* d\n    still d\n  c: 2\n";
+        assert_eq!(strip(Type::Sass, src), "\na\n  b: 1\n  \n  c: 2\n");
+    }
+}
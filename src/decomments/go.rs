@@ -0,0 +1,84 @@
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Line comments that the Go toolchain interprets.
+const DIRECTIVES: [&str; 5] = ["go:", "export ", "extern ", "line ", " +build"];
+
+// Whether the comments and blanks at the start of `rest` are followed by `import "C"`, which makes
+// the comment before them a cgo preamble.
+fn precedes_cgo_import(mut rest: &str) -> bool {
+    loop {
+        rest = rest.trim_start();
+        match rest.strip_prefix("//") {
+            Some(comment) => rest = comment.find('\n').map_or("", |i| &comment[i..]),
+            None => return rest.starts_with("import \"C\""),
+        }
+    }
+}
+
+// Scanner for Go: raw strings in backticks, rune literals, and comments that are really compiler
+// directives or cgo preambles.
+pub(crate) struct Go;
+
+impl Scan for Go {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if cursor.eat("//") {
+            let directive = DIRECTIVES.iter().any(|d| cursor.starts_with(d));
+            cursor.skip_until("\n");
+            let kind = match directive || precedes_cgo_import(cursor.rest()) {
+                true => CommentKind::Directive,
+                false => CommentKind::Line,
+            };
+            return Ok(Token::Comment { kind, open: 2, close: 0 });
+        }
+        if cursor.eat("/*") {
+            return match cursor.block_comment(CommentKind::Block, "/*", "*/", false, start)? {
+                Token::Comment { open, close, .. } if precedes_cgo_import(cursor.rest()) => {
+                    Ok(Token::Comment { kind: CommentKind::Directive, open, close })
+                }
+                token => Ok(token),
+            };
+        }
+        if let Some(token) = cursor.unmatched_close("/*", "*/") {
+            return token;
+        }
+
+        match cursor.bump() {
+            Some('`') => cursor.quoted("`", None, start),
+            Some('"') => cursor.quoted("\"", Some('\\'), start),
+            Some('\'') => cursor.quoted("'", Some('\\'), start),
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str) -> String {
+        Stripper::new(Type::Go).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn raw_strings_and_runes() {
+        assert_eq!(strip("s := `C:\\ // x` // c\nr := '\"' /* c */\n"), "s := `C:\\ // x` \nr := '\"' \n");
+        assert_eq!(strip("q := '\\'' // c\n"), "q := '\\'' \n");
+    }
+
+    #[test]
+    fn directives() {
+        let src = "//go:build linux\n// +build linux\n\n// Package a.\npackage a\n\n//go:generate stringer\n//export F\nfunc F() {} // c\n";
+        assert_eq!(strip(src), "//go:build linux\n// +build linux\n\n\npackage a\n\n//go:generate stringer\n//export F\nfunc F() {} \n");
+        let stripped = Stripper::new(Type::Go).keep_directives(false).strip_str(src).unwrap();
+        assert_eq!(stripped, "\n\n\n\npackage a\n\n\n\nfunc F() {} \n");
+    }
+
+    #[test]
+    fn cgo_preambles() {
+        let src = "// #include <stdio.h>\n// #cgo LDFLAGS: -lm\nimport \"C\"\n/* #include <a.h> */\n// c\nimport \"C\"\n// c\nimport \"fmt\"\n";
+        assert_eq!(strip(src), "// #include <stdio.h>\n// #cgo LDFLAGS: -lm\nimport \"C\"\n/* #include <a.h> */\n// c\nimport \"C\"\n\nimport \"fmt\"\n");
+    }
+}
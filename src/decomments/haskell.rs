@@ -0,0 +1,185 @@
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+fn is_symbol(c: char) -> bool {
+    match c {
+        '!' | '#' | '$' | '%' | '&' | '*' | '+' | '.' | '/' | '<' | '=' | '>' | '?' | '@' | '\\' | '^' | '|' | '-'
+        | '~' | ':' => true,
+        c if c.is_ascii() => false,
+        c => !c.is_alphanumeric() && !c.is_whitespace(),
+    }
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c == '_' || c == '\'' || c.is_alphanumeric()
+}
+
+// Scanner for Haskell: a run of two or more dashes starts a comment only if it is not part of an
+// operator such as `-->`, `{-# ... #-}` pragmas are directives, and Haddock comments are docs.
+pub(crate) struct Haskell;
+
+impl Haskell {
+    // Finishes a string literal, including gaps (`\` whitespace `\`), whose quote has been consumed.
+    fn string(cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+        loop {
+            match cursor.bump() {
+                Some('"') => return Ok(Token::Str),
+                Some('\\') if cursor.peek().is_some_and(char::is_whitespace) => {
+                    cursor.eat_while(char::is_whitespace);
+                    cursor.eat("\\");
+                }
+                Some('\\') => {
+                    cursor.bump();
+                }
+                Some('\n') if !cursor.recover => return Err(Fault::UnterminatedString { at: start }),
+                Some(_) => {}
+                None if cursor.recover => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+}
+
+impl Scan for Haskell {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if start == 0 && cursor.eat("#!") {
+            cursor.skip_until("\n");
+            return Ok(Token::Comment { kind: CommentKind::Directive, open: 2, close: 0 });
+        }
+        if cursor.eat("{-") {
+            let kind = match cursor.peek() {
+                Some('#') => CommentKind::Directive,
+                Some('|') => CommentKind::Doc,
+                Some(' ') if cursor.rest().trim_start_matches(' ').starts_with('|') => CommentKind::Doc,
+                _ => CommentKind::Block,
+            };
+            return cursor.block_comment(kind, "{-", "-}", true, start);
+        }
+        if let Some(token) = cursor.unmatched_close("{-", "-}") {
+            return token;
+        }
+
+        let Some(c) = cursor.peek() else { return Ok(Token::Code) };
+        match c {
+            c if is_symbol(c) => {
+                // operators are maximal runs of symbols; dashes alone make a comment
+                let run = cursor.eat_while(is_symbol);
+                if run.len() >= 2 && run.bytes().all(|b| b == b'-') {
+                    let doc = cursor.rest().trim_start_matches(' ').starts_with(['|', '^', '$', '*']);
+                    let kind = if doc { CommentKind::Doc } else { CommentKind::Line };
+                    cursor.skip_until("\n");
+                    return Ok(Token::Comment { kind, open: run.len(), close: 0 });
+                }
+                Ok(Token::Code)
+            }
+            '"' => {
+                cursor.bump();
+                Self::string(cursor, start)
+            }
+            '\'' => {
+                cursor.bump();
+                // `'x'` and `'\n'` are characters, `'Just` and `''Type` are promotions and quotes
+                if cursor.peek() == Some('\\') || cursor.peek_nth(1) == Some('\'') {
+                    return cursor.quoted("'", Some('\\'), start);
+                }
+                Ok(Token::Code)
+            }
+            c if is_ident_continue(c) => {
+                // primes inside identifiers such as `foldl'`
+                cursor.eat_while(is_ident_continue);
+                Ok(Token::Code)
+            }
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+// Scanner for literate Haskell: only bird-track lines (`> code`) or `\begin{code}` blocks are
+// Haskell, everything else is prose reported as documentation.
+pub(crate) struct Literate {
+    haskell: Haskell,
+    latex: Option<bool>, // whether code is delimited by `\begin{code}`, decided on the first call
+    in_code: bool,       // whether the cursor is within code
+}
+
+impl Literate {
+    pub(crate) fn new() -> Self {
+        Self { haskell: Haskell, latex: None, in_code: false }
+    }
+}
+
+impl Scan for Literate {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let latex = *self
+            .latex
+            .get_or_insert_with(|| cursor.rest().lines().any(|l| l.starts_with("\\begin{code}")));
+        let line_start = cursor.at_line_begin();
+
+        if line_start {
+            if latex {
+                let marker = if self.in_code { "\\end{code}" } else { "\\begin{code}" };
+                if cursor.starts_with(marker) {
+                    self.in_code = !self.in_code;
+                    cursor.skip_until("\n");
+                    return Ok(Token::Code);
+                }
+            } else {
+                self.in_code = cursor.eat(">");
+                if self.in_code {
+                    return Ok(Token::Code);
+                }
+            }
+        }
+
+        if self.in_code {
+            return self.haskell.scan(cursor);
+        }
+        if cursor.eat("\n") {
+            return Ok(Token::Code);
+        }
+        cursor.skip_until("\n");
+        Ok(Token::Comment { kind: CommentKind::Doc, open: 0, close: 0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str, lang: Type) -> String {
+        Stripper::new(lang).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn operators_and_comments() {
+        let src = "x --> y -- c\nz = a |-- b --- d\n";
+        assert_eq!(strip(src, Type::Haskell), "x --> y \nz = a |-- b \n");
+        assert_eq!(strip("a {- b {- c -} d -} e\n", Type::Haskell), "a  e\n");
+    }
+
+    #[test]
+    fn pragmas_and_haddock() {
+        let src = "{-# LANGUAGE GADTs #-}\n-- | Doc.\nf :: Int -- ^ arg\n{- | More. -}\n";
+        assert_eq!(strip(src, Type::Haskell), "{-# LANGUAGE GADTs #-}\n\nf :: Int \n\n");
+        assert_eq!(Stripper::new(Type::Haskell).keep_docs(true).strip_str(src).unwrap(), src);
+    }
+
+    #[test]
+    fn literals() {
+        let src = "s = \"a -- \\\n   \\b\" -- c\nc = '\"' ; f' = foldl' g 'Just -- d\n";
+        assert_eq!(strip(src, Type::Haskell), "s = \"a -- \\\n   \\b\" \nc = '\"' ; f' = foldl' g 'Just \n");
+    }
+
+    #[test]
+    fn literate() {
+        let bird = "Prose -- here.\n> main = f -- c\n\nMore.\n";
+        assert_eq!(strip(bird, Type::LiterateHaskell), "\n> main = f \n\n\n");
+        let latex = "Prose.\n\\begin{code}\nx = 1 -- c\n\\end{code}\n";
+        assert_eq!(strip(latex, Type::LiterateHaskell), "\n\\begin{code}\nx = 1 \n\\end{code}\n");
+    }
+}
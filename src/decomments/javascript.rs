@@ -0,0 +1,301 @@
+use super::rust::is_ident_continue;
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Keywords after which a `/` starts a regular expression rather than a division.
+const KEYWORDS: [&str; 15] = [
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw", "case", "do", "else",
+    "yield", "await", "extends",
+];
+
+fn is_ident_char(c: char) -> bool {
+    c == '$' || is_ident_continue(c)
+}
+
+// Whether a comment body is a notice that minifiers preserve: `/*! ... */`, `@license` or `@preserve`.
+fn is_legal(text: &str) -> bool {
+    text.starts_with('!') || text.contains("@license") || text.contains("@preserve")
+}
+
+// What the braces and angle brackets around the cursor belong to.
+enum Nest {
+    Template(usize), // a `${...}` hole of a template literal, with the braces opened inside it
+    Jsx(usize),      // a `{...}` expression of a JSX element, with the braces opened inside it
+    Tag,             // a JSX tag, whose `>` or `/>` has yet to come
+    Children,        // the children of a JSX element, up to its closing tag
+}
+
+// Scanner for JavaScript and TypeScript: template literals with nested `${...}` expressions,
+// regular expression literals, license comments and, unless TypeScript type assertions such as
+// `<T>x` rule them out, JSX elements, whose text is code.
+pub(crate) struct JavaScript {
+    nests: Vec<Nest>,
+    regex_ok: bool, // whether a `/` here would start a regular expression, or a `<` a JSX element
+    jsx: bool,      // whether JSX elements are recognised
+}
+
+impl JavaScript {
+    pub(crate) fn new(jsx: bool) -> Self {
+        Self { nests: Vec::new(), regex_ok: true, jsx }
+    }
+
+    // Lexes the body of a template literal up to its end or to the next `${`.
+    fn template(&mut self, cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+        loop {
+            if cursor.eat("`") {
+                self.regex_ok = false;
+                return Ok(Token::Str);
+            }
+            if cursor.eat("${") {
+                self.nests.push(Nest::Template(0));
+                self.regex_ok = true;
+                return Ok(Token::Str);
+            }
+            match cursor.bump() {
+                Some('\\') => {
+                    cursor.bump();
+                }
+                Some(_) => {}
+                None if cursor.recover => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+
+    // Lexes a regular expression literal whose opening `/` has been consumed; returns false,
+    // leaving the cursor where it was, if it ends with its line.
+    fn regex(cursor: &mut Cursor<'_>) -> bool {
+        let resume = cursor.pos();
+        let mut class = false;
+        loop {
+            match cursor.bump() {
+                Some('\\') => {
+                    cursor.bump();
+                }
+                Some('[') => class = true,
+                Some(']') => class = false,
+                Some('/') if !class => {
+                    cursor.eat_while(is_ident_char);
+                    return true;
+                }
+                Some('\n') | None => {
+                    cursor.rewind(resume);
+                    return false;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    // Whether the `<` at the cursor opens a JSX element rather than the type parameters of a generic
+    // arrow function, written `<T,>` or `<T extends U>` in TSX.
+    fn opens_element(cursor: &Cursor<'_>) -> bool {
+        let rest = &cursor.rest()[1..];
+        let name = rest.find(|c: char| !is_ident_char(c)).map_or(rest, |i| &rest[..i]);
+        match rest[name.len()..].chars().next() {
+            _ if name.is_empty() => rest.starts_with('>'),
+            Some(',') => false,
+            _ => !rest[name.len()..].trim_start().starts_with("extends "),
+        }
+    }
+
+    // Lexes the inside of a JSX tag: its name, attributes, quoted values and `{...}` expressions.
+    fn tag(&mut self, cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+        match cursor.bump() {
+            Some(quote @ ('"' | '\'')) => return cursor.quoted(quote.encode_utf8(&mut [0; 4]), None, start),
+            Some('{') => {
+                self.nests.push(Nest::Jsx(0));
+                self.regex_ok = true;
+            }
+            // a self-closing element ends with its tag, and an operator may follow it
+            Some('/') if cursor.eat(">") => {
+                self.nests.pop();
+                self.regex_ok = false;
+            }
+            Some('>') => {
+                self.nests.pop();
+                self.nests.push(Nest::Children);
+            }
+            _ => {}
+        }
+        Ok(Token::Code)
+    }
+
+    // Lexes the children of a JSX element: text up to the next tag or `{...}` expression, which is
+    // code, or the next tag itself.
+    fn children(&mut self, cursor: &mut Cursor<'_>) -> Token {
+        if cursor.eat("</") {
+            cursor.skip_until(">");
+            cursor.eat(">");
+            self.nests.pop();
+            self.regex_ok = false;
+        } else if cursor.eat("<") {
+            self.nests.push(Nest::Tag);
+        } else if cursor.eat("{") {
+            self.nests.push(Nest::Jsx(0));
+            self.regex_ok = true;
+        } else {
+            cursor.bump();
+            cursor.eat_while(|c| c != '<' && c != '{');
+        }
+        Token::Code
+    }
+}
+
+impl Scan for JavaScript {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if let Some(Nest::Children) = self.nests.last() {
+            return Ok(self.children(cursor));
+        }
+        if cursor.eat("//") {
+            cursor.skip_until("\n");
+            let kind = match is_legal(&cursor.before()[start + 2..]) {
+                true => CommentKind::Legal,
+                false => CommentKind::Line,
+            };
+            return Ok(Token::Comment { kind, open: 2, close: 0 });
+        }
+        if cursor.eat("/*") {
+            let doc = cursor.eat_doc_star();
+            return match cursor.block_comment(CommentKind::Block, "/*", "*/", false, start)? {
+                Token::Comment { open, close, .. } => {
+                    let kind = match &cursor.before()[start + open..cursor.pos() - close] {
+                        text if is_legal(text) => CommentKind::Legal,
+                        _ if doc => CommentKind::Doc,
+                        _ => CommentKind::Block,
+                    };
+                    Ok(Token::Comment { kind, open, close })
+                }
+                token => Ok(token),
+            };
+        }
+        if start == 0 && cursor.eat("#!") {
+            // hashbang line
+            cursor.skip_until("\n");
+            return Ok(Token::Code);
+        }
+
+        if let Some(Nest::Tag) = self.nests.last() {
+            return self.tag(cursor, start);
+        }
+
+        let Some(c) = cursor.peek() else { return Ok(Token::Code) };
+        if c.is_whitespace() {
+            cursor.bump();
+            return Ok(Token::Code);
+        }
+
+        match (self.nests.last_mut(), c) {
+            (Some(Nest::Template(depth) | Nest::Jsx(depth)), '{') => *depth += 1,
+            (Some(Nest::Template(depth) | Nest::Jsx(depth)), '}') if *depth > 0 => *depth -= 1,
+            (Some(Nest::Template(_)), '}') => {
+                // end of the embedded expression: the template resumes
+                self.nests.pop();
+                cursor.bump();
+                return self.template(cursor, start);
+            }
+            (Some(Nest::Jsx(_)), '}') => {
+                // end of the embedded expression: the element resumes
+                self.nests.pop();
+                cursor.bump();
+                return Ok(Token::Code);
+            }
+            _ => {}
+        }
+
+        match c {
+            '<' if self.jsx && self.regex_ok && Self::opens_element(cursor) => {
+                cursor.bump();
+                self.nests.push(Nest::Tag);
+                Ok(Token::Code)
+            }
+            '`' => {
+                cursor.bump();
+                self.template(cursor, start)
+            }
+            '"' | '\'' => {
+                cursor.bump();
+                self.regex_ok = false;
+                cursor.quoted(if c == '"' { "\"" } else { "'" }, Some('\\'), start)
+            }
+            '/' => {
+                cursor.bump();
+                if self.regex_ok && Self::regex(cursor) {
+                    self.regex_ok = false;
+                    return Ok(Token::Str);
+                }
+                self.regex_ok = true;
+                Ok(Token::Code)
+            }
+            c if is_ident_char(c) => {
+                let word = cursor.eat_while(is_ident_char);
+                self.regex_ok = KEYWORDS.contains(&word);
+                Ok(Token::Code)
+            }
+            ')' | ']' => {
+                cursor.bump();
+                self.regex_ok = false;
+                Ok(Token::Code)
+            }
+            _ => {
+                cursor.bump();
+                // `a++ / b` divides
+                self.regex_ok = !(cursor.before().ends_with("++") || cursor.before().ends_with("--"));
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str) -> String {
+        Stripper::new(Type::JavaScript).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn template_literals() {
+        assert_eq!(strip("let s = `// ${a /* c */} ${`${b}`} /*`; // c\n"), "let s = `// ${a } ${`${b}`} /*`; \n");
+        assert_eq!(strip("let o = `${ {a: 1} }`; // c\n"), "let o = `${ {a: 1} }`; \n");
+    }
+
+    #[test]
+    fn regular_expressions() {
+        assert_eq!(strip("let r = /\\/\\/ [/*]/g; // c\n"), "let r = /\\/\\/ [/*]/g; \n");
+        assert_eq!(strip("return /'/.test(s); // c\n"), "return /'/.test(s); \n");
+        assert_eq!(strip("let q = a / b; // c\nlet p = i++ / 2; // d\n"), "let q = a / b; \nlet p = i++ / 2; \n");
+    }
+
+    #[test]
+    fn license_comments() {
+        let src = "/*! v1 (c) A */\n// @license MIT\n/** Doc. */\n// c\nf();\n";
+        assert_eq!(strip(src), "/*! v1 (c) A */\n// @license MIT\n\n\nf();\n");
+        assert_eq!(Stripper::new(Type::TypeScript).keep_legal(false).strip_str(src).unwrap(), "\n\n\n\nf();\n");
+    }
+
+    #[test]
+    fn hashbang() {
+        assert_eq!(strip("#!/usr/bin/env node\n// c\n"), "#!/usr/bin/env node\n\n");
+    }
+
+    #[test]
+    fn jsx() {
+        assert_eq!(strip("const a = <p>Don't click</p>; // c1\n"), "const a = <p>Don't click</p>; \n");
+        assert_eq!(strip("<a>http://x.com</a>"), "<a>http://x.com</a>");
+        let src = "return (<>\n  <A b=\"//\" c={d /* e */ > 1} />\n  {/* f */}{g ? <i>it's</i> : `${h}`}\n</>) / 2; // j\n";
+        assert_eq!(strip(src), "return (<>\n  <A b=\"//\" c={d  > 1} />\n  {}{g ? <i>it's</i> : `${h}`}\n</>) / 2; \n");
+        assert_eq!(strip("if (a < b) { c = d > e; } // f\n"), "if (a < b) { c = d > e; } \n");
+    }
+
+    #[test]
+    fn type_assertions() {
+        let ts = "let a = <T>b; // it's\nlet c = <U,>(d: U) => d; // e\n";
+        assert_eq!(Stripper::new(Type::TypeScript).strip_str(ts).unwrap(), "let a = <T>b; \nlet c = <U,>(d: U) => d; \n");
+        let tsx = "let c = <U,>(d: U) => <p>{d}'s</p>; // e\n";
+        assert_eq!(Stripper::new(Type::Tsx).strip_str(tsx).unwrap(), "let c = <U,>(d: U) => <p>{d}'s</p>; \n");
+    }
+}
@@ -0,0 +1,202 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// JVM languages handled by the Jvm scanner.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    Java,
+    Kotlin,
+    Groovy,
+    Scala,
+}
+
+// Shape of a string literal.
+#[derive(Copy, Clone)]
+struct Literal {
+    quote: &'static str,
+    escapes: bool,   // whether `\` escapes the next character
+    templates: bool, // whether `${...}` embeds an expression
+}
+
+// A template expression being lexed as code.
+struct Hole {
+    literal: Literal,
+    depth: usize, // braces opened inside the hole
+}
+
+// Scanner for Java, Kotlin, Groovy and Scala: Javadoc, triple-quoted text blocks and raw strings,
+// `${...}` string templates and, except in Java and Groovy, nesting block comments.
+pub(crate) struct Jvm {
+    flavor: Flavor,
+    holes: Vec<Hole>,
+}
+
+impl Jvm {
+    pub(crate) fn new(flavor: Flavor) -> Self {
+        Self { flavor, holes: Vec::new() }
+    }
+
+    // Lexes the body of a string literal up to its end or to the next template expression.
+    fn body(&mut self, cursor: &mut Cursor<'_>, literal: Literal, start: usize) -> Result<Token, Fault> {
+        loop {
+            if cursor.starts_with(literal.quote) {
+                // quotes right before the closing `"""` belong to the string
+                let quotes = match literal.quote.len() {
+                    3 => cursor.rest().bytes().take_while(|&b| b == literal.quote.as_bytes()[0]).count(),
+                    n => n,
+                };
+                cursor.skip(quotes);
+                return Ok(Token::Str);
+            }
+            if literal.templates && cursor.eat("${") {
+                self.holes.push(Hole { literal, depth: 0 });
+                return Ok(Token::Str);
+            }
+            match cursor.bump() {
+                Some('\\') if literal.escapes => {
+                    cursor.bump();
+                }
+                Some(_) => {}
+                None if cursor.recover => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+}
+
+impl Scan for Jvm {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        let flavor = self.flavor;
+
+        if cursor.eat("//") {
+            return Ok(cursor.line_comment(CommentKind::Line, 2));
+        }
+        if cursor.eat("/*") {
+            // `/**` starts Javadoc, KDoc and Scaladoc
+            let kind = match cursor.eat_doc_star() {
+                true => CommentKind::Doc,
+                false => CommentKind::Block,
+            };
+            let nests = matches!(flavor, Flavor::Kotlin | Flavor::Scala);
+            return cursor.block_comment(kind, "/*", "*/", nests, start);
+        }
+        if let Some(token) = cursor.unmatched_close("/*", "*/") {
+            return token;
+        }
+
+        let Some(c) = cursor.peek() else { return Ok(Token::Code) };
+
+        if let Some(hole) = self.holes.last_mut() {
+            match c {
+                '{' => hole.depth += 1,
+                '}' if hole.depth > 0 => hole.depth -= 1,
+                '}' => {
+                    // end of the template expression: the string resumes
+                    let literal = self.holes.pop().unwrap().literal;
+                    cursor.bump();
+                    return self.body(cursor, literal, start);
+                }
+                _ => {}
+            }
+        }
+
+        match c {
+            '"' | '\'' => {
+                let triple = if c == '"' { "\"\"\"" } else { "'''" };
+                let literal = if cursor.starts_with(triple) && (c == '"' || flavor == Flavor::Groovy) {
+                    cursor.skip(3);
+                    Literal {
+                        quote: triple,
+                        escapes: matches!(flavor, Flavor::Java | Flavor::Groovy),
+                        templates: c == '"' && matches!(flavor, Flavor::Kotlin | Flavor::Groovy),
+                    }
+                } else if c == '"' {
+                    cursor.bump();
+                    Literal {
+                        quote: "\"",
+                        escapes: true,
+                        templates: matches!(flavor, Flavor::Kotlin | Flavor::Groovy),
+                    }
+                } else if flavor == Flavor::Groovy {
+                    cursor.bump();
+                    Literal { quote: "'", escapes: true, templates: false }
+                } else {
+                    cursor.bump();
+                    // in Scala, `'a` without a closing quote is a symbol
+                    if flavor == Flavor::Scala && cursor.peek() != Some('\\') && cursor.peek_nth(1) != Some('\'') {
+                        cursor.eat_while(is_ident_continue);
+                        return Ok(Token::Code);
+                    }
+                    return cursor.quoted("'", Some('\\'), start);
+                };
+                self.body(cursor, literal, start)
+            }
+            '`' => {
+                // quoted identifier
+                cursor.bump();
+                cursor.eat_while(|c| c != '`' && c != '\n');
+                cursor.eat("`");
+                Ok(Token::Code)
+            }
+            c if is_ident_start(c) => {
+                cursor.eat_while(is_ident_continue);
+                // Scala interpolated strings such as `s"..."` and `f"""..."""`
+                if flavor == Flavor::Scala && cursor.peek() == Some('"') {
+                    let quote = if cursor.starts_with("\"\"\"") { "\"\"\"" } else { "\"" };
+                    cursor.skip(quote.len());
+                    let literal = Literal { quote, escapes: quote.len() == 1, templates: true };
+                    return self.body(cursor, literal, start);
+                }
+                Ok(Token::Code)
+            }
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str, lang: Type) -> String {
+        Stripper::new(lang).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn text_blocks() {
+        let src = "String s = \"\"\"\n  // not a comment \\\"\"\"\n  \"\"\"; // c\n";
+        assert_eq!(strip(src, Type::Java), "String s = \"\"\"\n  // not a comment \\\"\"\"\n  \"\"\"; \n");
+        assert_eq!(strip("val s = \"\"\"a\\\"\"\"\" // c\n", Type::Kotlin), "val s = \"\"\"a\\\"\"\"\" \n");
+    }
+
+    #[test]
+    fn templates() {
+        let src = "val s = \"${f(\"// x\") /* c */} // y\" // c\n";
+        assert_eq!(strip(src, Type::Kotlin), "val s = \"${f(\"// x\") } // y\" \n");
+        assert_eq!(strip("def s = '''/* ${a} */''' // c\n", Type::Groovy), "def s = '''/* ${a} */''' \n");
+        assert_eq!(strip("val s = s\"${a /* c */} // b\" // c\n", Type::Scala), "val s = s\"${a } // b\" \n");
+    }
+
+    #[test]
+    fn nesting() {
+        assert_eq!(strip("a /* b /* c */ d */ e", Type::Kotlin), "a  e");
+        assert_eq!(strip("a /* b /* c */ d", Type::Java), "a  d");
+    }
+
+    #[test]
+    fn javadoc() {
+        let src = "/** Doc. */\n/**/ /*** c */ int x;\n";
+        assert_eq!(strip(src, Type::Java), "\n  int x;\n");
+        assert_eq!(Stripper::new(Type::Java).keep_docs(true).strip_str(src).unwrap(), "/** Doc. */\n  int x;\n");
+    }
+
+    #[test]
+    fn scala_symbols_and_chars() {
+        assert_eq!(strip("val a = 'sym; val b = '\"'; // c\n", Type::Scala), "val a = 'sym; val b = '\"'; \n");
+    }
+}
@@ -0,0 +1,290 @@
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Dialects of Lisp handled by the Lisp scanner.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    CommonLisp,
+    Scheme, // also Racket
+    Clojure, // also ClojureScript and EDN
+    EmacsLisp,
+}
+
+impl Flavor {
+    // Whether `#| ... |#` comments and `|quoted symbols|` exist.
+    fn has_blocks(self) -> bool {
+        matches!(self, Flavor::CommonLisp | Flavor::Scheme)
+    }
+
+    // Prefix of the comments that discard the next form: `#;` in Scheme and `#_` in Clojure.
+    fn discard(self) -> Option<&'static str> {
+        match self {
+            Flavor::Scheme => Some("#;"),
+            Flavor::Clojure => Some("#_"),
+            _ => None,
+        }
+    }
+}
+
+// Whether `c` ends a symbol or a number.
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | '"' | ';')
+}
+
+// Scanner for Common Lisp, Scheme, Racket, Clojure and Emacs Lisp: `;` comments, nesting `#| ... |#`
+// blocks, and `#;` and `#_`, which comment out the next whole form. Character literals such as `#\;`,
+// `\;` and `?\"` are strings. In Emacs Lisp, `-*-` lines, `;;;###autoload` cookies and the Local
+// Variables block at the end of a file are directives, since they change how it is loaded.
+pub(crate) struct Lisp {
+    flavor: Flavor,
+    local_variables: bool, // inside an Emacs Local Variables block
+}
+
+impl Lisp {
+    pub(crate) fn new(flavor: Flavor) -> Self {
+        Self { flavor, local_variables: false }
+    }
+
+    // Consumes a character literal at the cursor, if there is one.
+    fn character(&self, cursor: &mut Cursor<'_>) -> bool {
+        let prefix = match self.flavor {
+            Flavor::Clojure => "\\",
+            Flavor::EmacsLisp => "?",
+            Flavor::CommonLisp | Flavor::Scheme => "#\\",
+        };
+        // `?` also ends predicates such as `null?`, and `\` escapes symbol characters in Emacs Lisp
+        let word_start = cursor.before().chars().next_back().is_none_or(|c| is_delimiter(c) || "'`,@#^".contains(c));
+        if !word_start || !cursor.eat(prefix) {
+            return false;
+        }
+        if self.flavor == Flavor::EmacsLisp && cursor.eat("\\") {
+            cursor.bump();
+            return true;
+        }
+        // named characters such as `#\space` or `\newline`
+        if cursor.bump().is_some_and(char::is_alphanumeric) {
+            cursor.eat_while(char::is_alphanumeric);
+        }
+        true
+    }
+
+    // Skips the blanks and comments at the cursor.
+    fn skip_blanks(&self, cursor: &mut Cursor<'_>) -> Result<(), Fault> {
+        loop {
+            let start = cursor.pos();
+            cursor.eat_while(|c| c.is_whitespace() || (c == ',' && self.flavor == Flavor::Clojure));
+            if cursor.eat(";") {
+                cursor.skip_until("\n");
+            } else if self.flavor.has_blocks() && cursor.eat("#|") {
+                cursor.block_comment(CommentKind::Block, "#|", "|#", true, start)?;
+            } else if cursor.pos() == start {
+                return Ok(());
+            }
+        }
+    }
+
+    // Consumes the next form, with the blanks and comments before it: a list, a string, a character,
+    // an atom, or a form behind reader macros such as `'`, `#'` or `#?`.
+    fn form(&self, cursor: &mut Cursor<'_>, open: &'static str, at: usize) -> Result<(), Fault> {
+        self.skip_blanks(cursor)?;
+        let start = cursor.pos();
+        if self.character(cursor) {
+            return Ok(());
+        }
+        if let Some(discard) = self.flavor.discard().filter(|discard| cursor.eat(discard)) {
+            // the discarded form is skipped along with the next one
+            self.form(cursor, discard, start)?;
+            return self.form(cursor, open, at);
+        }
+        let Some(c) = cursor.peek() else {
+            return match cursor.recovering() {
+                true => Ok(()),
+                false => Err(Fault::UnterminatedComment { open: open.into(), at }),
+            };
+        };
+        match c {
+            '(' | '[' | '{' => {
+                cursor.bump();
+                let close = match c {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                };
+                loop {
+                    self.skip_blanks(cursor)?;
+                    match cursor.peek() {
+                        Some(c) if c == close => {
+                            cursor.bump();
+                            return Ok(());
+                        }
+                        Some(')' | ']' | '}') if !cursor.recovering() => {
+                            return Err(Fault::UnterminatedComment { open: open.into(), at });
+                        }
+                        Some(')' | ']' | '}') => {
+                            cursor.bump();
+                        }
+                        Some(_) => self.form(cursor, open, at)?,
+                        None if cursor.recovering() => return Ok(()),
+                        None => return Err(Fault::UnterminatedComment { open: open.into(), at }),
+                    }
+                }
+            }
+            '"' => {
+                cursor.bump();
+                cursor.quoted("\"", Some('\\'), start).map(drop)
+            }
+            '|' if self.flavor.has_blocks() => {
+                cursor.bump();
+                cursor.quoted("|", Some('\\'), start).map(drop)
+            }
+            '\'' | '`' | '~' | '@' | ',' => {
+                cursor.bump();
+                cursor.eat("@");
+                self.form(cursor, open, at)
+            }
+            // metadata applies to the form after it
+            '^' if self.flavor == Flavor::Clojure => {
+                cursor.bump();
+                self.form(cursor, open, at)?;
+                self.form(cursor, open, at)
+            }
+            '#' => {
+                cursor.bump();
+                match cursor.peek() {
+                    Some('(' | '[' | '{' | '"') => self.form(cursor, open, at),
+                    Some('\'' | '?' | '=') => {
+                        cursor.bump();
+                        cursor.eat("@");
+                        self.form(cursor, open, at)
+                    }
+                    // a tagged literal such as `#inst "2024-01-01"`, or a namespaced map such as `#:a{:b 1}`
+                    Some(c) if (c.is_alphabetic() || c == ':') && self.flavor == Flavor::Clojure => {
+                        cursor.eat_while(|c| !is_delimiter(c));
+                        self.form(cursor, open, at)
+                    }
+                    _ => {
+                        cursor.eat_while(|c| !is_delimiter(c));
+                        Ok(())
+                    }
+                }
+            }
+            ')' | ']' | '}' => match cursor.recovering() {
+                true => Ok(()),
+                false => Err(Fault::UnterminatedComment { open: open.into(), at }),
+            },
+            _ => {
+                while let Some(c) = cursor.peek().filter(|&c| !is_delimiter(c)) {
+                    cursor.bump();
+                    if c == '\\' {
+                        cursor.bump();
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Classifies a `;` comment whose text follows the cursor.
+    fn line_kind(&mut self, cursor: &Cursor<'_>) -> CommentKind {
+        if self.flavor != Flavor::EmacsLisp {
+            return CommentKind::Line;
+        }
+        let text = cursor.rest().split('\n').next().unwrap_or_default().trim_start_matches(';').trim();
+        let first_line = !cursor.before().contains('\n');
+        if text.starts_with("Local Variables:") {
+            self.local_variables = true;
+        }
+        let directive = self.local_variables || text.starts_with("###autoload") || (first_line && text.contains("-*-"));
+        if text.starts_with("End:") {
+            self.local_variables = false;
+        }
+        match directive {
+            true => CommentKind::Directive,
+            false => CommentKind::Line,
+        }
+    }
+}
+
+impl Scan for Lisp {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if start == 0 && cursor.eat("#!") {
+            // shebang line
+            cursor.skip_until("\n");
+            return Ok(Token::Code);
+        }
+        if cursor.starts_with(";") {
+            let kind = self.line_kind(cursor);
+            cursor.skip(1);
+            return Ok(cursor.line_comment(kind, 1));
+        }
+        if self.flavor.has_blocks() && cursor.eat("#|") {
+            return cursor.block_comment(CommentKind::Block, "#|", "|#", true, start);
+        }
+        if let Some(discard) = self.flavor.discard().filter(|discard| cursor.eat(discard)) {
+            self.form(cursor, discard, start)?;
+            return Ok(Token::Comment { kind: CommentKind::Block, open: discard.len(), close: 0 });
+        }
+        if self.character(cursor) {
+            return Ok(Token::Str);
+        }
+
+        match cursor.bump() {
+            Some('"') => cursor.quoted("\"", Some('\\'), start),
+            Some('|') if self.flavor.has_blocks() => cursor.quoted("|", Some('\\'), start),
+            // escaped symbol characters, as in `a\;b`
+            Some('\\') => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(lang: Type, src: &str) -> String {
+        Stripper::new(lang).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn unclosed_discarded_form() {
+        let clojure = Stripper::new(Type::Clojure);
+        assert!(clojure.strip_str("(def x #_(foo").is_err());
+        assert_eq!(clojure.clone().recover(true).strip_str("(def x #_(foo").unwrap(), "(def x ");
+        let scheme = Stripper::new(Type::Scheme);
+        assert!(scheme.strip_str("#;(foo").is_err());
+        assert_eq!(scheme.recover(true).strip_str("#;(foo (bar").unwrap(), "");
+    }
+
+    #[test]
+    fn block_comments() {
+        assert_eq!(strip(Type::CommonLisp, "(a #| b #| c |# d |# e) ; f\n"), "(a  e) \n");
+        assert_eq!(strip(Type::Scheme, "(a '|; b| \"; c\") ; d\n"), "(a '|; b| \"; c\") \n");
+        assert_eq!(strip(Type::Clojure, "(a #| b) ; c\n"), "(a #| b) \n");
+    }
+
+    #[test]
+    fn characters() {
+        assert_eq!(strip(Type::CommonLisp, "(list #\\; #\\\" #\\space) ; c\n"), "(list #\\; #\\\" #\\space) \n");
+        assert_eq!(strip(Type::Clojure, "[\\; \\\" a\\;b] ; c\n"), "[\\; \\\" a\\;b] \n");
+        assert_eq!(strip(Type::EmacsLisp, "(list ?\\; ?\\\" null?) ; c\n"), "(list ?\\; ?\\\" null?) \n");
+    }
+
+    #[test]
+    fn discarded_forms() {
+        assert_eq!(crate::strip_str("(a #_(b [c]) d)", Type::Clojure).unwrap(), "(a  d)");
+        assert_eq!(crate::strip_str("(a #; #;b c d)", Type::Scheme).unwrap(), "(a  d)");
+        assert_eq!(strip(Type::Clojure, "{:a #_ ^:m \"x)\" 1}"), "{:a  1}");
+    }
+
+    #[test]
+    fn emacs_directives() {
+        let src = ";;; a.el --- b -*- lexical-binding: t -*-\n;; c\n;;;###autoload\n(f)\n;; Local Variables:\n;; x: 1\n;; End:\n";
+        assert_eq!(strip(Type::EmacsLisp, src), ";;; a.el --- b -*- lexical-binding: t -*-\n\n;;;###autoload\n(f)\n;; Local Variables:\n;; x: 1\n;; End:\n");
+    }
+}
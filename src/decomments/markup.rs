@@ -0,0 +1,308 @@
+use super::{Host, Type};
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Elements whose content is not HTML.
+const RAW_TEXT: [&str; 4] = ["script", "style", "pre", "textarea"];
+
+// Whether the body of an HTML comment is an IE conditional comment or a server-side include.
+fn is_directive(body: &str) -> bool {
+    body.starts_with("[if") || body.starts_with("<![endif]") || body.starts_with("#")
+}
+
+// Returns the value of the attribute `name` in the text of a start tag.
+fn attribute<'t>(tag: &'t str, name: &str) -> Option<&'t str> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(name) {
+        let at = from + i;
+        from = at + name.len();
+        let value = tag[from..].trim_start();
+        if !tag[..at].ends_with(char::is_whitespace) || !value.starts_with('=') {
+            continue;
+        }
+        let value = value[1..].trim_start();
+        return value.chars().next().map(|c| match c {
+            '"' | '\'' => value[1..].split(c).next().unwrap(),
+            _ => value.split(|c: char| c.is_whitespace() || c == '>').next().unwrap(),
+        });
+    }
+    None
+}
+
+// Language of the content of a `<script>` or `<style>` element, if it is lexed.
+fn guest(name: &str, tag: &str) -> Option<Type> {
+    if name == "style" {
+        return match attribute(tag, "lang").or_else(|| attribute(tag, "type")).map(str::to_ascii_lowercase).as_deref() {
+            None | Some("css" | "text/css") => Some(Type::Css),
+            Some("scss" | "text/scss") => Some(Type::Scss),
+            Some("less" | "text/less") => Some(Type::Less),
+            Some("sass" | "text/sass") => Some(Type::Sass),
+            _ => None,
+        };
+    }
+    if name != "script" {
+        return None;
+    }
+    match attribute(tag, "lang").or_else(|| attribute(tag, "type")).map(str::to_ascii_lowercase).as_deref() {
+        None | Some("js" | "jsx" | "javascript" | "module" | "text/javascript" | "text/babel" | "text/jsx") => {
+            Some(Type::JavaScript)
+        }
+        Some("ts" | "typescript" | "text/typescript") => Some(Type::TypeScript),
+        Some("tsx") => Some(Type::Tsx),
+        _ => None,
+    }
+}
+
+// Dialects of HTML handled by the Markup scanner.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    Html,
+    Vue,
+    Svelte,
+    Astro,
+}
+
+// Scanner for HTML and single-file components: quotes delimit strings only inside tags, the
+// content of `<pre>` and `<textarea>` is left alone, and conditional comments and server-side
+// includes are directives. Scripts, styles and the frontmatter of Astro components are regions
+// of their own language; the content of other raw text elements is left alone too.
+pub(crate) struct Markup {
+    flavor: Flavor,
+    tag: Option<(String, usize)>,  // name of the tag being lexed, lowercased, and where it starts
+    raw: Option<String>,           // closing tag that ends the raw text being skipped
+    region: Option<(Type, usize)>, // region opened by the last token
+}
+
+impl Markup {
+    pub(crate) fn new(flavor: Flavor) -> Self {
+        Self { flavor, tag: None, raw: None, region: None }
+    }
+}
+
+impl Host for Markup {
+    fn region(&mut self) -> Option<(Type, usize)> {
+        self.region.take()
+    }
+}
+
+impl Scan for Markup {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if let Some(close) = self.raw.take() {
+            match cursor.rest().to_ascii_lowercase().find(&close) {
+                Some(i) => cursor.skip(i),
+                None => cursor.skip_to_end(),
+            }
+            return Ok(Token::Code);
+        }
+
+        if let Some((name, tag_start)) = &self.tag {
+            return match cursor.bump() {
+                Some(quote @ ('"' | '\'')) => cursor.quoted(if quote == '"' { "\"" } else { "'" }, None, start),
+                Some('>') => {
+                    if RAW_TEXT.contains(&name.as_str()) && !cursor.before().ends_with("/>") {
+                        let close = format!("</{}", name);
+                        let end = cursor.rest().to_ascii_lowercase().find(&close).map_or(cursor.rest().len(), |i| i);
+                        match guest(name, &cursor.before()[*tag_start..]) {
+                            Some(lang) => self.region = Some((lang, cursor.pos() + end)),
+                            None => self.raw = Some(close),
+                        }
+                    }
+                    self.tag = None;
+                    Ok(Token::Code)
+                }
+                _ => Ok(Token::Code),
+            };
+        }
+
+        if start == 0 && self.flavor == Flavor::Astro && cursor.eat("---") {
+            // frontmatter script
+            let end = cursor.rest().find("\n---").map_or(cursor.rest().len(), |i| i + 1);
+            self.region = Some((Type::TypeScript, cursor.pos() + end));
+            return Ok(Token::Code);
+        }
+
+        if cursor.eat("<!--") {
+            let directive = is_directive(cursor.rest());
+            if !cursor.skip_until("-->") && !cursor.recover {
+                return Err(Fault::UnterminatedComment { open: "<!--".into(), at: start });
+            }
+            let close = if cursor.eat("-->") { 3 } else { 0 };
+            let kind = if directive { CommentKind::Directive } else { CommentKind::Block };
+            return Ok(Token::Comment { kind, open: 4, close });
+        }
+
+        if cursor.eat("<") {
+            let closing = cursor.eat("/");
+            let name = cursor.eat_while(|c| c.is_alphanumeric() || c == '-' || c == ':');
+            if !name.is_empty() {
+                // attributes of end tags are ignored, but their quotes still apply
+                self.tag = Some((if closing { String::new() } else { name.to_ascii_lowercase() }, start));
+            } else if cursor.starts_with("!") || cursor.starts_with("?") {
+                // doctype or processing instruction
+                cursor.skip_until(">");
+            }
+            return Ok(Token::Code);
+        }
+
+        cursor.bump();
+        cursor.eat_while(|c| c != '<');
+        Ok(Token::Code)
+    }
+}
+
+// Scanner for XML: CDATA sections are string literals, processing instructions are left alone, and
+// comments are also recognised in the internal subset of a DOCTYPE.
+pub(crate) struct Xml {
+    in_tag: bool,  // inside a start or end tag
+    doctype: bool, // inside a DOCTYPE declaration
+    subset: bool,  // inside the internal subset of the DOCTYPE
+}
+
+impl Xml {
+    pub(crate) fn new() -> Self {
+        Self { in_tag: false, doctype: false, subset: false }
+    }
+
+    // Lexes a comment starting at the cursor.
+    fn comment(&mut self, cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+        cursor.skip(4);
+        if !cursor.skip_until("-->") && !cursor.recover {
+            return Err(Fault::UnterminatedComment { open: "<!--".into(), at: start });
+        }
+        let close = if cursor.eat("-->") { 3 } else { 0 };
+        Ok(Token::Comment { kind: CommentKind::Block, open: 4, close })
+    }
+}
+
+impl Scan for Xml {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if self.in_tag || self.doctype {
+            return match cursor.peek() {
+                Some('<') if self.subset && cursor.starts_with("<!--") => self.comment(cursor, start),
+                Some(quote @ ('"' | '\'')) => {
+                    cursor.bump();
+                    cursor.quoted(if quote == '"' { "\"" } else { "'" }, None, start)
+                }
+                Some(c) => {
+                    cursor.bump();
+                    match c {
+                        '[' if self.doctype => self.subset = true,
+                        ']' if self.doctype => self.subset = false,
+                        '>' if !self.subset => {
+                            self.in_tag = false;
+                            self.doctype = false;
+                        }
+                        _ => {}
+                    }
+                    Ok(Token::Code)
+                }
+                None => Ok(Token::Code),
+            };
+        }
+
+        if cursor.starts_with("<!--") {
+            return self.comment(cursor, start);
+        }
+        if cursor.eat("<![CDATA[") {
+            return cursor.quoted("]]>", None, start);
+        }
+        if cursor.eat("<?") {
+            cursor.skip_until("?>");
+            cursor.eat("?>");
+            return Ok(Token::Code);
+        }
+        if cursor.eat("<!DOCTYPE") {
+            self.doctype = true;
+            return Ok(Token::Code);
+        }
+        if cursor.eat("<") {
+            self.in_tag = true;
+            return Ok(Token::Code);
+        }
+
+        cursor.bump();
+        cursor.eat_while(|c| c != '<');
+        Ok(Token::Code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str) -> String {
+        Stripper::new(Type::Markup).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn text_and_attributes() {
+        assert_eq!(strip("<p title=\"<!-- x -->\">Don't <!-- c --></p>"), "<p title=\"<!-- x -->\">Don't </p>");
+        assert_eq!(strip("<a href='#'>it's</a><!---->"), "<a href='#'>it's</a>");
+    }
+
+    #[test]
+    fn raw_text_elements() {
+        let src = "<script>var s = \"<!-- x -->\";</script><pre> <!-- y --> </pre><!-- c -->";
+        assert_eq!(strip(src), "<script>var s = \"<!-- x -->\";</script><pre> <!-- y --> </pre>");
+        assert_eq!(strip("<SCRIPT src=a.js></Script><!-- c -->"), "<SCRIPT src=a.js></Script>");
+    }
+
+    #[test]
+    fn directives() {
+        let src = "<!--[if IE]><p>IE</p><![endif]--><!--#include virtual=\"a.html\" --><!-- c -->";
+        assert_eq!(strip(src), "<!--[if IE]><p>IE</p><![endif]--><!--#include virtual=\"a.html\" -->");
+        assert_eq!(Stripper::new(Type::Markup).keep_directives(false).strip_str(src).unwrap(), "");
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        assert!(Stripper::new(Type::Markup).strip_str("<p>a<!-- b").is_err());
+        assert_eq!(Stripper::new(Type::Markup).recover(true).strip_str("<p>a<!-- b").unwrap(), "<p>a");
+    }
+
+    #[test]
+    fn xml() {
+        let xml = Stripper::new(Type::Xml);
+        let src = "<?xml version=\"1.0\"?><!-- c --><a b=\"<!-- x -->\"><![CDATA[<!-- y -->]]></a>";
+        assert_eq!(xml.strip_str(src).unwrap(), "<?xml version=\"1.0\"?><a b=\"<!-- x -->\"><![CDATA[<!-- y -->]]></a>");
+        assert_eq!(xml.strip_str("<?pi <!-- x --> ?><a/>").unwrap(), "<?pi <!-- x --> ?><a/>");
+        assert!(xml.strip_str("<a><![CDATA[x</a>").is_err());
+    }
+
+    #[test]
+    fn doctype_subset() {
+        let src = "<!DOCTYPE a [\n<!-- c -->\n<!ENTITY e \"<!-- x -->\">\n]><a>&e;</a>";
+        let out = "<!DOCTYPE a [\n\n<!ENTITY e \"<!-- x -->\">\n]><a>&e;</a>";
+        assert_eq!(Stripper::new(Type::Xml).strip_str(src).unwrap(), out);
+    }
+
+    #[test]
+    fn scripts() {
+        let src = "<script>// c\nlet a = '</p>'; /* d */</script><!-- e --><script type=\"text/plain\">// f</script>";
+        let out = "<script>\nlet a = '</p>'; </script><script type=\"text/plain\">// f</script>";
+        assert_eq!(strip(src), out);
+        assert!(Stripper::new(Type::Markup).strip_str("<script>/* open</script><p>").is_err());
+    }
+
+    #[test]
+    fn components() {
+        let vue = "<template><p>{{ a }}</p><!-- c --></template>\n<script lang=\"ts\">let a: number = 1 // d\n</script>";
+        let out = "<template><p>{{ a }}</p></template>\n<script lang=\"ts\">let a: number = 1 \n</script>";
+        assert_eq!(Stripper::new(Type::Vue).strip_str(vue).unwrap(), out);
+        let astro = "---\nconst a = 1; // c\n---\n<p>{a}</p><!-- d -->";
+        assert_eq!(Stripper::new(Type::Astro).strip_str(astro).unwrap(), "---\nconst a = 1; \n---\n<p>{a}</p>");
+        let svelte = "<script>let a = 1 /* c */</script><p>{a}</p>";
+        assert_eq!(Stripper::new(Type::Svelte).strip_str(svelte).unwrap(), "<script>let a = 1 </script><p>{a}</p>");
+    }
+
+    #[test]
+    fn styles() {
+        let src = "<style>a { b: url(//x) } /* c */</style><style lang=\"scss\">// d\n</style><!-- e -->";
+        assert_eq!(strip(src), "<style>a { b: url(//x) } </style><style lang=\"scss\">\n</style>");
+    }
+}
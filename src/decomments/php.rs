@@ -0,0 +1,171 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use super::Type;
+use crate::language::Language;
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Comment prefixes that tools act upon, such as `// phpcs:ignore` or `/** @phpstan-ignore-next-line */`.
+const DIRECTIVES: [&str; 3] = ["phpcs:", "@phpstan-", "@psalm-"];
+
+// Returns the offset of the next tag that opens PHP code in `text`: `<?php`, `<?=` or a short `<?`
+// followed by a blank, which leaves out XML declarations.
+fn open_tag(text: &str) -> Option<usize> {
+    text.match_indices("<?").map(|(i, _)| i).find(|&i| {
+        let tag = &text[i + 2..];
+        tag.get(..3).is_some_and(|name| name.eq_ignore_ascii_case("php")) || tag.starts_with('=') || tag.starts_with(char::is_whitespace)
+    })
+}
+
+// Finishes a heredoc or nowdoc whose `<<<` has been consumed. Since PHP 7.3 the closing identifier may
+// be indented and followed by more code on its line.
+fn heredoc(cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+    cursor.eat_while(|c| c == ' ' || c == '\t');
+    if !cursor.eat("'") {
+        cursor.eat("\"");
+    }
+    let word = cursor.eat_while(is_ident_continue);
+    if !word.starts_with(is_ident_start) {
+        return Ok(Token::Code);
+    }
+    cursor.skip_until("\n");
+    while cursor.eat("\n") {
+        let body = cursor.rest().trim_start_matches([' ', '\t']);
+        if body.strip_prefix(word).is_some_and(|rest| !rest.starts_with(is_ident_continue)) {
+            cursor.skip(cursor.rest().len() - body.len() + word.len());
+            return Ok(Token::Str);
+        }
+        cursor.skip_until("\n");
+    }
+    match cursor.recovering() {
+        true => Ok(Token::Str),
+        false => Err(Fault::UnterminatedString { at: start }),
+    }
+}
+
+// Scanner for PHP: `//` and `#` comments also end before `?>`, `#[...]` is an attribute, heredocs and
+// nowdocs are strings, and the HTML outside `<?php ... ?>` is lexed as such. PHP code runs even inside
+// HTML comments, so one that it cuts off is kept.
+pub(crate) struct Php {
+    php: bool,                            // inside `<?php ... ?>`
+    html: Option<(Box<dyn Scan>, usize)>, // scanner of the HTML being lexed, and where it ends
+}
+
+impl Php {
+    pub(crate) fn new() -> Self {
+        Self { php: false, html: None }
+    }
+
+    // Lexes the HTML at the cursor, up to the next opening tag.
+    fn html(&mut self, cursor: &mut Cursor<'_>) -> Option<Result<Token, Fault>> {
+        let start = cursor.pos();
+        let (scanner, end) = self.html.get_or_insert_with(|| {
+            let end = start + open_tag(cursor.rest()).unwrap_or(cursor.rest().len());
+            (Type::Markup.scanner(), end)
+        });
+        if start == *end {
+            self.html = None;
+            return None;
+        }
+        // elements and strings that go on after the PHP code are not malformed
+        let cut = *end < start + cursor.rest().len();
+        let recover = cursor.recover;
+        cursor.recover |= cut;
+        let token = cursor.within(*end, |cursor| scanner.scan(cursor));
+        cursor.recover = recover;
+        match token {
+            Ok(Token::Comment { close: 0, .. }) if cut && cursor.pos() == *end => Some(Ok(Token::Code)),
+            token => Some(token),
+        }
+    }
+}
+
+impl Scan for Php {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if !self.php {
+            if let Some(token) = self.html(cursor) {
+                return token;
+            }
+            // at an opening tag
+            cursor.skip(2);
+            if !cursor.eat("=") {
+                cursor.skip(cursor.rest().get(..3).filter(|name| name.eq_ignore_ascii_case("php")).map_or(0, str::len));
+            }
+            self.php = true;
+            return Ok(Token::Code);
+        }
+
+        if cursor.eat("?>") {
+            self.php = false;
+            return Ok(Token::Code);
+        }
+        if cursor.eat("#[") {
+            return Ok(Token::Code);
+        }
+        if cursor.eat("//") || cursor.eat("#") {
+            let open = cursor.pos() - start;
+            let kind = match DIRECTIVES.iter().any(|d| cursor.rest().trim_start_matches(' ').starts_with(d)) {
+                true => CommentKind::Directive,
+                false => CommentKind::Line,
+            };
+            // a line comment also ends before the closing tag, which is still code
+            let line = cursor.rest().split('\n').next().unwrap_or_default();
+            let end = cursor.pos() + line.find("?>").unwrap_or(line.len());
+            return Ok(cursor.within(end, |cursor| cursor.line_comment(kind, open)));
+        }
+        if cursor.eat("/*") {
+            let kind = match cursor.rest() {
+                rest if DIRECTIVES.iter().any(|d| rest.trim_start_matches(['*', ' ']).starts_with(d)) => CommentKind::Directive,
+                _ if cursor.eat_doc_star() => CommentKind::Doc,
+                _ => CommentKind::Block,
+            };
+            return cursor.block_comment(kind, "/*", "*/", false, start);
+        }
+        if cursor.eat("<<<") {
+            return heredoc(cursor, start);
+        }
+
+        match cursor.bump() {
+            Some(quote @ ('\'' | '"' | '`')) => cursor.quoted(quote.encode_utf8(&mut [0; 4]), Some('\\'), start),
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str) -> String {
+        Stripper::new(Type::Php).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn closing_tag_ends_line_comments() {
+        assert_eq!(strip("<?php echo 1; // c ?><p>a</p>"), "<?php echo 1; ?><p>a</p>");
+        assert_eq!(strip("<?php $a = '?>'; # c\n#[Attr]\nf();"), "<?php $a = '?>'; \n#[Attr]\nf();");
+    }
+
+    #[test]
+    fn heredocs() {
+        let src = "<?php\n$a = <<<EOT\n  // kept {$b}\n  EOT;\n$c = <<<'N'\n# kept\nN; // c\n";
+        assert_eq!(strip(src), "<?php\n$a = <<<EOT\n  // kept {$b}\n  EOT;\n$c = <<<'N'\n# kept\nN; \n");
+        assert!(Stripper::new(Type::Php).strip_str("<?php $a = <<<EOT\nopen\n").is_err());
+    }
+
+    #[test]
+    fn html_regions() {
+        let src = "<?xml version=\"1.0\"?><!-- c --><p title=\"<?= $t ?>\"><?= $a /* d */ ?></p>";
+        assert_eq!(strip(src), "<?xml version=\"1.0\"?><p title=\"<?= $t ?>\"><?= $a  ?></p>");
+        // PHP code inside an HTML comment still runs
+        assert_eq!(strip("<!-- <?php f(); ?> --><!-- e -->"), "<!-- <?php f(); ?> -->");
+    }
+
+    #[test]
+    fn docs_and_directives() {
+        let src = "<?php\n/** Doc. */\n// phpcs:ignore\n/* @psalm-suppress X */\nf();";
+        assert_eq!(strip(src), "<?php\n\n// phpcs:ignore\n/* @psalm-suppress X */\nf();");
+        assert_eq!(Stripper::new(Type::Php).keep_docs(true).strip_str(src).unwrap(), src);
+    }
+}
@@ -0,0 +1,246 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Comment prefixes that tools act upon, such as `# type: ignore` or `# noqa`.
+const DIRECTIVES: [&str; 6] = ["type:", "noqa", "pragma", "pylint:", "fmt:", "isort:"];
+
+// Shape of a string literal.
+#[derive(Copy, Clone)]
+struct Literal {
+    quote: &'static str, // `'`, `"`, `'''` or `"""`
+    fstring: bool,
+}
+
+// A replacement field of an f-string being lexed as code.
+struct Hole {
+    literal: Literal,
+    depth: usize, // brackets opened inside the field
+    spec: bool,   // whether the field is nested in a format specifier
+}
+
+// Scanner for Python: string prefixes, f-strings with nested quotes, and docstrings, which are
+// reported as documentation comments while other triple-quoted strings stay string literals.
+pub(crate) struct Python {
+    holes: Vec<Hole>,
+    depth: usize,      // brackets opened outside of f-strings
+    expects_doc: bool, // whether a string here would be a module, class or function docstring
+    header: bool,      // whether the current logical line starts with `def` or `class`
+    line_start: bool,  // whether the next token starts a logical line
+    closed: bool,      // whether the last string body ended at its closing quote
+}
+
+impl Python {
+    pub(crate) fn new() -> Self {
+        Self {
+            holes: Vec::new(),
+            depth: 0,
+            expects_doc: true,
+            header: false,
+            line_start: true,
+            closed: false,
+        }
+    }
+
+    // Lexes the body of a string up to its end or, in an f-string, to the next replacement field.
+    fn body(&mut self, cursor: &mut Cursor<'_>, literal: Literal, mut spec: bool, start: usize) -> Result<Token, Fault> {
+        self.closed = false;
+        loop {
+            if cursor.eat(literal.quote) {
+                self.closed = true;
+                return Ok(Token::Str);
+            }
+            if literal.fstring {
+                if !spec && (cursor.eat("{{") || cursor.eat("}}")) {
+                    continue;
+                }
+                if cursor.eat("{") {
+                    self.holes.push(Hole { literal, depth: 0, spec });
+                    return Ok(Token::Str);
+                }
+                if spec && cursor.eat("}") {
+                    spec = false;
+                    continue;
+                }
+            }
+            match cursor.bump() {
+                Some('\\') => {
+                    cursor.bump();
+                }
+                Some('\n') if literal.quote.len() == 1 && !cursor.recover => {
+                    return Err(Fault::UnterminatedString { at: start });
+                }
+                Some(_) => {}
+                None if cursor.recover => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+
+    // Lexes a string literal whose prefix has been consumed and which starts at the cursor.
+    fn string(&mut self, cursor: &mut Cursor<'_>, prefix: &str, start: usize) -> Result<Token, Fault> {
+        let quote = match cursor.peek() {
+            Some('"') if cursor.starts_with("\"\"\"") => "\"\"\"",
+            Some('\'') if cursor.starts_with("'''") => "'''",
+            Some('"') => "\"",
+            _ => "'",
+        };
+        let docstring = self.expects_doc && self.holes.is_empty() && quote.len() == 3 && !prefix.contains(['f', 'F', 'b', 'B']);
+        cursor.skip(quote.len());
+        let literal = Literal { quote, fstring: prefix.contains(['f', 'F']) };
+        let token = self.body(cursor, literal, false, start)?;
+
+        // A docstring is an expression statement on its own
+        let rest = cursor.rest();
+        let trailer = rest[..rest.find('\n').unwrap_or(rest.len())].trim_start();
+        if docstring && (trailer.is_empty() || trailer.starts_with('#')) && matches!(token, Token::Str) {
+            let open = cursor.before()[start..].find(quote).unwrap() + quote.len();
+            // a docstring left open by the end of the input has no closing quote
+            let close = if self.closed { quote.len() } else { 0 };
+            return Ok(Token::Comment { kind: CommentKind::Doc, open, close });
+        }
+        Ok(token)
+    }
+
+    // Records a token that is not a comment or blank, for the tracking of logical lines.
+    fn significant(&mut self, c: char) {
+        if self.line_start {
+            self.header = false;
+        }
+        self.line_start = false;
+        self.expects_doc = c == ':' && self.depth == 0 && self.header;
+    }
+}
+
+impl Scan for Python {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        let Some(c) = cursor.peek() else { return Ok(Token::Code) };
+
+        match c {
+            '#' => {
+                cursor.bump();
+                let body = cursor.rest().trim_start_matches(' ');
+                let line = cursor.before().matches('\n').count();
+                let directive = DIRECTIVES.iter().any(|d| body.starts_with(d))
+                    || (start == 0 && body.starts_with('!'))
+                    || (line < 2 && body.contains("coding") && (body.contains("coding:") || body.contains("coding=")));
+                cursor.skip_until("\n");
+                let kind = if directive { CommentKind::Directive } else { CommentKind::Line };
+                return Ok(Token::Comment { kind, open: 1, close: 0 });
+            }
+            '\\' if cursor.peek_nth(1) == Some('\n') => {
+                // explicit line joining
+                cursor.skip(2);
+                return Ok(Token::Code);
+            }
+            '\n' => {
+                cursor.bump();
+                if self.depth == 0 && self.holes.is_empty() {
+                    self.line_start = true;
+                }
+                return Ok(Token::Code);
+            }
+            c if c.is_whitespace() => {
+                cursor.bump();
+                return Ok(Token::Code);
+            }
+            _ => {}
+        }
+
+        if let Some(hole) = self.holes.last_mut() {
+            match c {
+                '{' | '(' | '[' => hole.depth += 1,
+                ')' | ']' => hole.depth = hole.depth.saturating_sub(1),
+                '}' if hole.depth > 0 => hole.depth -= 1,
+                '}' | ':' if hole.depth == 0 && !cursor.starts_with(":=") => {
+                    // end of the expression: the rest of the field is string text
+                    let Hole { literal, spec, .. } = self.holes.pop().unwrap();
+                    cursor.bump();
+                    return self.body(cursor, literal, spec || c == ':', start);
+                }
+                _ => {}
+            }
+        }
+
+        if is_ident_start(c) {
+            let word = cursor.eat_while(is_ident_continue);
+            let prefix = word.to_ascii_lowercase();
+            let is_prefix = matches!(prefix.as_str(), "r" | "u" | "f" | "b" | "t" | "br" | "rb" | "fr" | "rf" | "tr" | "rt");
+            if is_prefix && matches!(cursor.peek(), Some('"' | '\'')) {
+                let token = self.string(cursor, word, start);
+                self.significant('"');
+                return token;
+            }
+            let first = self.line_start;
+            self.significant(c);
+            if first {
+                self.header = matches!(word, "def" | "class" | "async");
+            }
+            return Ok(Token::Code);
+        }
+
+        match c {
+            '"' | '\'' => {
+                let token = self.string(cursor, "", start);
+                self.significant(c);
+                token
+            }
+            _ => {
+                cursor.bump();
+                match c {
+                    '(' | '[' | '{' => self.depth += 1,
+                    ')' | ']' | '}' => self.depth = self.depth.saturating_sub(1),
+                    _ => {}
+                }
+                self.significant(c);
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn strip(src: &str) -> String {
+        Stripper::new(Type::Python).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn docstrings() {
+        let python = Stripper::new(Type::Python);
+        assert_eq!(python.strip_str("def f():\n    \"\"\"Doc.\"\"\"\n    return 1\n").unwrap(), "def f():\n    \n    return 1\n");
+        assert_eq!(python.strip_str("x = \"\"\"not a doc\"\"\"\n").unwrap(), "x = \"\"\"not a doc\"\"\"\n");
+        assert_eq!(strip("'''Module.'''\nclass A(\n    B,\n):\n    r'''Class.'''  # c\n"), "\nclass A(\n    B,\n):\n      \n");
+        assert_eq!(strip("f(\"\"\"arg\"\"\")\n\"\"\"after a call\"\"\"\n"), "f(\"\"\"arg\"\"\")\n\"\"\"after a call\"\"\"\n");
+    }
+
+    #[test]
+    fn pass_docstrings() {
+        let src = "class A:\n    \"\"\"Doc.\"\"\"\n";
+        assert_eq!(Stripper::new(Type::Python).pass_docstrings(true).strip_str(src).unwrap(), "class A:\n    pass\n");
+        assert_eq!(Stripper::new(Type::Python).keep_docs(true).strip_str(src).unwrap(), src);
+    }
+
+    #[test]
+    fn f_strings() {
+        assert_eq!(strip("s = f\"{d[\"#\"]} # {x!r:>{w}} {{#}}\"  # c\n"), "s = f\"{d[\"#\"]} # {x!r:>{w}} {{#}}\"  \n");
+        assert_eq!(strip("s = rb'\\d#' + f'{(lambda: 1)()}'  # c\n"), "s = rb'\\d#' + f'{(lambda: 1)()}'  \n");
+    }
+
+    #[test]
+    fn directives() {
+        let src = "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nimport a  # noqa: F401\nx = 1  # type: int\n# c\n";
+        assert_eq!(strip(src), "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nimport a  # noqa: F401\nx = 1  # type: int\n\n");
+    }
+
+    #[test]
+    fn unterminated_docstring() {
+        let python = Stripper::new(Type::Python);
+        assert!(python.strip_str("def f():\n    \"\"\"").is_err());
+        assert_eq!(python.clone().recover(true).strip_str("def f():\n    \"\"\"").unwrap(), "def f():\n    ");
+        assert_eq!(python.recover(true).strip_str("def f():\n    '''doc\\'''").unwrap(), "def f():\n    ");
+    }
+}
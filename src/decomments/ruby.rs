@@ -0,0 +1,348 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use super::shell::heredoc_bodies;
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Keywords after which a `/` starts a regular expression and a `%` a percent literal.
+const KEYWORDS: [&str; 16] = [
+    "if", "elsif", "unless", "while", "until", "and", "or", "not", "return", "when", "case", "then", "else",
+    "do", "in", "yield",
+];
+
+// Magic comments that change how the interpreter or tools read the file.
+const DIRECTIVES: [&str; 8] = [
+    "frozen_string_literal:", "encoding:", "coding:", "warn_indent:", "shareable_constant_value:", "-*-",
+    "typed:", "rubocop:",
+];
+
+// Blanks that may indent the terminator of a `<<~` or `<<-` here-document.
+const BLANKS: &[char] = &[' ', '\t'];
+
+// Shape of a string-like literal.
+#[derive(Copy, Clone)]
+struct Literal {
+    open: Option<char>, // bracket that nests inside the literal, as in `%q(a (b) c)`
+    close: char,
+    interpolates: bool, // whether `#{...}` embeds code
+    nesting: usize,     // brackets opened inside the literal
+}
+
+impl Literal {
+    fn new(close: char, interpolates: bool) -> Self {
+        Self { open: None, close, interpolates, nesting: 0 }
+    }
+}
+
+// An embedded `#{...}` being lexed as code.
+struct Hole {
+    literal: Literal,
+    depth: usize, // braces opened inside the hole
+}
+
+// Scanner for Ruby: interpolated strings, percent literals, here-documents, `=begin`/`=end` blocks,
+// the `__END__` data section and magic comments.
+pub(crate) struct Ruby {
+    holes: Vec<Hole>,
+    heredocs: Vec<(String, &'static [char])>, // here-documents opened on the current line
+    value: bool,                              // whether the last token ended an operand
+}
+
+impl Ruby {
+    pub(crate) fn new() -> Self {
+        Self { holes: Vec::new(), heredocs: Vec::new(), value: false }
+    }
+
+    // Lexes the body of a literal up to its end or to the next `#{`.
+    fn body(&mut self, cursor: &mut Cursor<'_>, mut literal: Literal, start: usize) -> Result<Token, Fault> {
+        self.value = true;
+        loop {
+            if literal.interpolates && cursor.eat("#{") {
+                self.holes.push(Hole { literal, depth: 0 });
+                self.value = false;
+                return Ok(Token::Str);
+            }
+            match cursor.bump() {
+                Some('\\') => {
+                    cursor.bump();
+                }
+                Some(c) if Some(c) == literal.open => literal.nesting += 1,
+                Some(c) if c == literal.close && literal.nesting > 0 => literal.nesting -= 1,
+                Some(c) if c == literal.close => return Ok(Token::Str),
+                Some(_) => {}
+                None if cursor.recovering() => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+
+    // Consumes the opening of a percent literal such as `%w(` or `%Q{` at the cursor and returns the
+    // literal, or leaves the cursor alone if the `%` is an operator.
+    fn percent(&self, cursor: &mut Cursor<'_>) -> Option<Literal> {
+        let start = cursor.pos();
+        let spaced = cursor.before().ends_with(char::is_whitespace);
+        cursor.bump();
+        let kind = cursor.peek().filter(|c| "qQwWiIrsx".contains(*c));
+        if kind.is_some() {
+            cursor.bump();
+        }
+        let delimiter = cursor.peek().filter(|c| !c.is_alphanumeric() && !c.is_whitespace());
+        let operand = cursor.peek_nth(1).is_some_and(|c| !c.is_whitespace());
+        // `x % (y)` is a modulo, `puts %(y)` a literal
+        let (Some(open), true) = (delimiter, !self.value || (spaced && operand)) else {
+            cursor.rewind(start);
+            return None;
+        };
+        cursor.bump();
+        let interpolates = !matches!(kind, Some('q' | 'w' | 'i' | 's'));
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            '<' => '>',
+            c => return Some(Literal::new(c, interpolates)),
+        };
+        Some(Literal { open: Some(open), ..Literal::new(close, interpolates) })
+    }
+
+    // Consumes the `<<~ID`, `<<-ID`, `<<ID` or quoted opening of a here-document at the cursor and
+    // records it, or leaves the cursor alone if the `<<` is an operator.
+    fn heredoc(&mut self, cursor: &mut Cursor<'_>) -> bool {
+        let start = cursor.pos();
+        let spaced = cursor.before().ends_with(char::is_whitespace);
+        cursor.skip(2);
+        let indented = cursor.eat("~") || cursor.eat("-");
+        let word = match cursor.peek() {
+            Some(quote @ ('"' | '\'' | '`')) => {
+                cursor.bump();
+                let word = cursor.eat_while(|c| c != quote && c != '\n');
+                cursor.eat(quote.encode_utf8(&mut [0; 4])).then_some(word)
+            }
+            // a bare `<<` is only taken for a here-document before a constant-like word, as in `<<EOS`
+            Some(c) if is_ident_start(c) && (indented || (c.is_uppercase() || c == '_') && (!self.value || spaced)) => {
+                Some(cursor.eat_while(is_ident_continue))
+            }
+            _ => None,
+        };
+        match word {
+            Some(word) if !word.is_empty() => {
+                self.heredocs.push((word.to_string(), if indented { BLANKS } else { &[] }));
+                true
+            }
+            _ => {
+                cursor.rewind(start);
+                false
+            }
+        }
+    }
+}
+
+// Whether the cursor is at the beginning of a line that starts with `word` alone or followed by blanks.
+fn line_starts_with(cursor: &Cursor<'_>, word: &str) -> bool {
+    cursor.at_line_begin()
+        && cursor.rest().strip_prefix(word).is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+impl Scan for Ruby {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if !self.heredocs.is_empty() && cursor.before().ends_with('\n') {
+            return heredoc_bodies(cursor, &mut self.heredocs, start);
+        }
+        if start == 0 && cursor.eat("#!") {
+            return Ok(cursor.line_comment(CommentKind::Directive, 2));
+        }
+        if line_starts_with(cursor, "__END__") {
+            // the data section, read through DATA
+            cursor.skip_to_end();
+            return Ok(Token::Str);
+        }
+        if line_starts_with(cursor, "=begin") {
+            cursor.skip(6);
+            loop {
+                if !cursor.skip_until("\n") {
+                    if cursor.recovering() {
+                        return Ok(Token::Comment { kind: CommentKind::Block, open: 6, close: 0 });
+                    }
+                    return Err(Fault::UnterminatedComment { open: "=begin".into(), at: start });
+                }
+                cursor.bump();
+                if line_starts_with(cursor, "=end") {
+                    let end = cursor.pos();
+                    cursor.skip_until("\n");
+                    return Ok(Token::Comment { kind: CommentKind::Block, open: 6, close: cursor.pos() - end });
+                }
+            }
+        }
+        if cursor.eat("#") {
+            let kind = match DIRECTIVES.iter().any(|d| cursor.rest().trim_start_matches(' ').starts_with(d)) {
+                true => CommentKind::Directive,
+                false => CommentKind::Line,
+            };
+            return Ok(cursor.line_comment(kind, 1));
+        }
+
+        let Some(c) = cursor.peek() else { return Ok(Token::Code) };
+        if c.is_whitespace() {
+            cursor.bump();
+            return Ok(Token::Code);
+        }
+
+        if let Some(hole) = self.holes.last_mut() {
+            match c {
+                '{' => hole.depth += 1,
+                '}' if hole.depth > 0 => hole.depth -= 1,
+                '}' => {
+                    // end of the embedded code: the literal resumes
+                    let literal = self.holes.pop().unwrap().literal;
+                    cursor.bump();
+                    return self.body(cursor, literal, start);
+                }
+                _ => {}
+            }
+        }
+
+        match c {
+            '"' | '`' => {
+                cursor.bump();
+                self.body(cursor, Literal::new(c, true), start)
+            }
+            '\'' => {
+                cursor.bump();
+                self.body(cursor, Literal::new(c, false), start)
+            }
+            '/' if !self.value => {
+                cursor.bump();
+                self.body(cursor, Literal::new('/', true), start)
+            }
+            '%' => match self.percent(cursor) {
+                Some(literal) => self.body(cursor, literal, start),
+                None => {
+                    cursor.bump();
+                    self.value = false;
+                    Ok(Token::Code)
+                }
+            },
+            '<' if cursor.starts_with("<<") && self.heredoc(cursor) => Ok(Token::Code),
+            // character literals such as `?#` or `?"`
+            '?' if !self.value && cursor.peek_nth(1).is_some_and(|c| !c.is_whitespace())
+                && !cursor.peek_nth(2).is_some_and(is_ident_continue) =>
+            {
+                cursor.bump();
+                if cursor.bump() == Some('\\') {
+                    cursor.bump();
+                }
+                self.value = true;
+                Ok(Token::Str)
+            }
+            // special globals such as `$"` or `$'`
+            '$' => {
+                cursor.bump();
+                match cursor.peek() {
+                    Some(c) if is_ident_continue(c) => {
+                        cursor.eat_while(is_ident_continue);
+                    }
+                    Some(_) => {
+                        cursor.bump();
+                    }
+                    None => {}
+                }
+                self.value = true;
+                Ok(Token::Code)
+            }
+            c if is_ident_start(c) || c == '@' => {
+                let word = cursor.eat_while(|c| is_ident_continue(c) || c == '@');
+                // predicate and bang methods such as `empty?`, but not `a != b`
+                if !cursor.starts_with("?=") && !cursor.starts_with("!=") && !cursor.eat("?") {
+                    cursor.eat("!");
+                }
+                self.value = !KEYWORDS.contains(&word);
+                Ok(Token::Code)
+            }
+            // numbers such as `1_000`, `2.5e-3`, `0x1F` or `3r`
+            c if c.is_ascii_digit() => {
+                loop {
+                    cursor.eat_while(is_ident_continue);
+                    let exponent = cursor.before().ends_with(['e', 'E']) && (cursor.starts_with("+") || cursor.starts_with("-"));
+                    let fraction = cursor.starts_with(".");
+                    if !(exponent || fraction) || !cursor.peek_nth(1).is_some_and(|c| c.is_ascii_digit()) {
+                        break;
+                    }
+                    cursor.bump();
+                }
+                self.value = true;
+                Ok(Token::Code)
+            }
+            ')' | ']' | '}' => {
+                cursor.bump();
+                self.value = true;
+                Ok(Token::Code)
+            }
+            _ => {
+                cursor.bump();
+                self.value = false;
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+
+    fn strip(src: &str) -> String {
+        crate::strip_str(src, Type::Ruby).unwrap()
+    }
+
+    #[test]
+    fn interpolation() {
+        assert_eq!(strip("\"a #{b # c\n} d\" # e\n"), "\"a #{b \n} d\" \n");
+        assert_eq!(strip("'#{a}' # c\n"), "'#{a}' \n");
+    }
+
+    #[test]
+    fn percent_literals() {
+        assert_eq!(strip("%w(a (#) b) # c\n"), "%w(a (#) b) \n");
+        assert_eq!(strip("%q{#} % 3 # c\n"), "%q{#} % 3 \n");
+        assert_eq!(strip("puts %(#) # c\n"), "puts %(#) \n");
+    }
+
+    #[test]
+    fn heredocs() {
+        let src = "x = <<~EOS # c\n  # kept\n  EOS\ny = 1 << 2 # d\n";
+        assert_eq!(strip(src), "x = <<~EOS \n  # kept\n  EOS\ny = 1 << 2 \n");
+    }
+
+    #[test]
+    fn block_comments_and_data() {
+        let src = "a\n=begin\nc\n=end\nb # c\n__END__\n# data\n";
+        assert_eq!(strip(src), "a\n\nb \n__END__\n# data\n");
+        assert!(crate::strip_str("=begin\nc\n", Type::Ruby).is_err());
+    }
+
+    #[test]
+    fn characters_and_directives() {
+        let src = "# frozen_string_literal: true\nc = ?# # d\n";
+        assert_eq!(strip(src), "# frozen_string_literal: true\nc = ?# \n");
+        assert_eq!(strip("$\" << x # c\n"), "$\" << x \n");
+    }
+
+    #[test]
+    fn division_after_number() {
+        assert_eq!(strip("10 / 2 # c\n"), "10 / 2 \n");
+        assert_eq!(strip("x * 100 / total # c\n"), "x * 100 / total \n");
+        assert_eq!(strip("y = 2.0 / x # c\n"), "y = 2.0 / x \n");
+        assert_eq!(strip("z = 1.5e-3 / x # c\n"), "z = 1.5e-3 / x \n");
+    }
+
+    #[test]
+    fn string_after_division() {
+        let src = "y = 100 / n\npath = \"a/b # not a comment\"\n";
+        assert_eq!(strip(src), src);
+    }
+
+    #[test]
+    fn regex_after_operator() {
+        assert_eq!(strip("x = a + /#{b} # c/ # d\n"), "x = a + /#{b} # c/ \n");
+    }
+}
@@ -0,0 +1,123 @@
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Scanner for Rust: nested and doc comments, raw, byte and C strings, and lifetimes, which share
+// their opening quote with char literals.
+pub(crate) struct Rust;
+
+pub(crate) fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_alphabetic()
+}
+
+pub(crate) fn is_ident_continue(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
+
+// Finishes a raw string such as `r#"..."#` once its prefix has been consumed; returns None,
+// consuming nothing, if the hashes are not followed by a quote (a raw identifier like `r#type`).
+fn raw_string(cursor: &mut Cursor<'_>, start: usize) -> Option<Result<Token, Fault>> {
+    let hashes = cursor.rest().bytes().take_while(|&b| b == b'#').count();
+    if cursor.rest()[hashes..].starts_with('"') {
+        cursor.skip(hashes + 1);
+        let close = format!("\"{}", "#".repeat(hashes));
+        Some(cursor.quoted(&close, None, start))
+    } else {
+        None
+    }
+}
+
+impl Scan for Rust {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if cursor.eat("//") {
+            // `///` and `//!` are doc comments, but `////` is not
+            let doc = cursor.starts_with("!") || (cursor.starts_with("/") && !cursor.starts_with("//"));
+            return Ok(match doc {
+                true => cursor.line_comment(CommentKind::Doc, 3),
+                false => cursor.line_comment(CommentKind::Line, 2),
+            });
+        }
+        if cursor.eat("/*") {
+            // `/**` and `/*!` are doc comments, but `/**/` and `/***` are not
+            let kind = match cursor.eat("!") || cursor.eat_doc_star() {
+                true => CommentKind::Doc,
+                false => CommentKind::Block,
+            };
+            return cursor.block_comment(kind, "/*", "*/", true, start);
+        }
+        if let Some(token) = cursor.unmatched_close("/*", "*/") {
+            return token;
+        }
+
+        match cursor.peek() {
+            Some(c) if is_ident_start(c) => {
+                let word = cursor.eat_while(is_ident_continue);
+                match (word, cursor.peek()) {
+                    ("r" | "br" | "cr", Some('"' | '#')) => raw_string(cursor, start).unwrap_or(Ok(Token::Code)),
+                    ("b" | "c", Some('"')) => {
+                        cursor.bump();
+                        cursor.quoted("\"", Some('\\'), start)
+                    }
+                    ("b", Some('\'')) => {
+                        cursor.bump();
+                        cursor.quoted("'", Some('\\'), start)
+                    }
+                    _ => Ok(Token::Code),
+                }
+            }
+            Some('"') => {
+                cursor.bump();
+                cursor.quoted("\"", Some('\\'), start)
+            }
+            Some('\'') => {
+                cursor.bump();
+                // `'a'` and `'\n'` are char literals, `'a` is a lifetime or a label
+                if cursor.peek() == Some('\\') || cursor.peek_nth(1) == Some('\'') {
+                    return cursor.quoted("'", Some('\\'), start);
+                }
+                cursor.eat_while(is_ident_continue);
+                Ok(Token::Code)
+            }
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+
+    fn strip(src: &str) -> String {
+        crate::strip_str(src, Type::Rust).unwrap()
+    }
+
+    #[test]
+    fn lifetimes_and_chars() {
+        assert_eq!(strip("fn f<'a>(x: &'a str) -> char { '\"' } // c\n"), "fn f<'a>(x: &'a str) -> char { '\"' } \n");
+        assert_eq!(strip("let c = '/'; let d = '\\''; /* c */\n"), "let c = '/'; let d = '\\''; \n");
+        assert_eq!(strip("'outer: loop { break 'outer; } // c\n"), "'outer: loop { break 'outer; } \n");
+    }
+
+    #[test]
+    fn raw_strings() {
+        assert_eq!(strip("let s = r\"/* \\\"; // c\n"), "let s = r\"/* \\\"; \n");
+        assert_eq!(strip("let s = r#\"a \"// b\" c\"#; // c\n"), "let s = r#\"a \"// b\" c\"#; \n");
+        assert_eq!(strip("let s = r###\"\"## /* \"###; // c\n"), "let s = r###\"\"## /* \"###; \n");
+        assert_eq!(strip("let r#type = 1; // c\n"), "let r#type = 1; \n");
+    }
+
+    #[test]
+    fn byte_strings() {
+        assert_eq!(strip("let b = b\"// \\\"\"; let c = b'\"'; // c\n"), "let b = b\"// \\\"\"; let c = b'\"'; \n");
+        assert_eq!(strip("let b = br#\"/* \"#; let c = c\"// \"; // c\n"), "let b = br#\"/* \"#; let c = c\"// \"; \n");
+    }
+
+    #[test]
+    fn nested_comments() {
+        assert_eq!(strip("a /* b /* c */ d */ e\n"), "a  e\n");
+        assert!(crate::strip_str("a /* b /* c */ d\n", Type::Rust).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use super::rust::is_ident_continue;
+use crate::lexer::{CommentKind, Cursor, Fault, Scan, Token};
+
+// Comment prefixes that tools act upon, such as `# shellcheck disable=SC2086`.
+const DIRECTIVES: [&str; 1] = ["shellcheck "];
+
+// Characters after which a word starts, so that a `#` there opens a comment.
+fn ends_word(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')' | '<' | '>')
+}
+
+// Consumes the `<<WORD`, `<<-WORD`, `<<'WORD'` or `<<\WORD` operator of a here-document at the cursor
+// and returns its delimiter and whether leading tabs are stripped from its lines, or leaves the cursor
+// alone if there is no such operator.
+pub(crate) fn heredoc(cursor: &mut Cursor<'_>) -> Option<(String, bool)> {
+    let start = cursor.pos();
+    cursor.skip(2);
+    let strip_tabs = cursor.eat("-");
+    cursor.eat_while(|c| c == ' ' || c == '\t');
+    cursor.eat("\\");
+    let quote = cursor.peek().filter(|&c| c == '"' || c == '\'');
+    if let Some(quote) = quote {
+        cursor.bump();
+        let word = cursor.eat_while(|c| c != quote && c != '\n');
+        if !word.is_empty() && cursor.eat(quote.encode_utf8(&mut [0; 4])) {
+            return Some((word.to_string(), strip_tabs));
+        }
+    } else {
+        let word = cursor.eat_while(|c| is_ident_continue(c) || c == '-' || c == '.');
+        if !word.is_empty() {
+            return Some((word.to_string(), strip_tabs));
+        }
+    }
+    cursor.rewind(start);
+    None
+}
+
+// Blanks that may indent the terminator of a here-document opened with `<<-`.
+pub(crate) const TABS: &[char] = &['\t'];
+
+// Consumes the bodies of the pending here-documents, which start at the cursor, as a single string.
+// Each is given by its delimiter and the characters that may indent its terminator line.
+pub(crate) fn heredoc_bodies(cursor: &mut Cursor<'_>, pending: &mut Vec<(String, &[char])>, start: usize) -> Result<Token, Fault> {
+    for (word, indent) in pending.drain(..) {
+        loop {
+            if cursor.is_empty() {
+                return match cursor.recovering() {
+                    true => Ok(Token::Str),
+                    false => Err(Fault::UnterminatedString { at: start }),
+                };
+            }
+            let line = cursor.rest().split('\n').next().unwrap_or_default();
+            let line_len = line.len();
+            let line = line.trim_end_matches('\r');
+            let line = line.trim_start_matches(indent);
+            cursor.skip(line_len);
+            cursor.eat("\n");
+            if line == word {
+                break;
+            }
+        }
+    }
+    Ok(Token::Str)
+}
+
+// Consumes the rest of a double-quoted string, which may hold command substitutions with quotes of their
+// own, as in `"$(echo "a # b")"`. Returns whether it was closed.
+fn skip_double_quoted(cursor: &mut Cursor<'_>) -> bool {
+    loop {
+        match cursor.bump() {
+            Some('"') => return true,
+            Some('\\') => {
+                cursor.bump();
+            }
+            Some('$') if cursor.eat("(") => {
+                if !skip_substitution(cursor) {
+                    return false;
+                }
+            }
+            Some('`') => {
+                if !skip_backquoted(cursor) {
+                    return false;
+                }
+            }
+            Some(_) => {}
+            None => return false,
+        }
+    }
+}
+
+// Consumes the rest of a `$(...)` command substitution. Returns whether it was closed.
+fn skip_substitution(cursor: &mut Cursor<'_>) -> bool {
+    let mut depth = 0;
+    loop {
+        let closed = match cursor.bump() {
+            Some(')') if depth == 0 => return true,
+            Some(')') => {
+                depth -= 1;
+                true
+            }
+            Some('(') => {
+                depth += 1;
+                true
+            }
+            Some('\\') => cursor.bump().is_some(),
+            Some('\'') => cursor.skip_until("'") && cursor.eat("'"),
+            Some('"') => skip_double_quoted(cursor),
+            Some('`') => skip_backquoted(cursor),
+            Some(_) => true,
+            None => false,
+        };
+        if !closed {
+            return false;
+        }
+    }
+}
+
+// Consumes the rest of a backquoted command substitution. Returns whether it was closed.
+fn skip_backquoted(cursor: &mut Cursor<'_>) -> bool {
+    loop {
+        match cursor.bump() {
+            Some('`') => return true,
+            Some('\\') => {
+                cursor.bump();
+            }
+            Some(_) => {}
+            None => return false,
+        }
+    }
+}
+
+// Lexes a double-quoted string whose opening quote, at `start`, has been consumed.
+fn double_quoted(cursor: &mut Cursor<'_>, start: usize) -> Result<Token, Fault> {
+    match skip_double_quoted(cursor) || cursor.recovering() {
+        true => Ok(Token::Str),
+        false => Err(Fault::UnterminatedString { at: start }),
+    }
+}
+
+// Scanner for POSIX shells, Bash and Zsh: `#` only starts a comment at the start of a word, quotes
+// protect it, and here-document bodies are left alone. The shebang line is code, so it is always kept.
+pub(crate) struct Shell {
+    heredocs: Vec<(String, &'static [char])>, // here-documents opened on the current line
+    arithmetic: usize,                        // `((` opened, inside which `<<` is a shift
+}
+
+impl Shell {
+    pub(crate) fn new() -> Self {
+        Self { heredocs: Vec::new(), arithmetic: 0 }
+    }
+}
+
+impl Scan for Shell {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+
+        if !self.heredocs.is_empty() && cursor.before().ends_with('\n') {
+            return heredoc_bodies(cursor, &mut self.heredocs, start);
+        }
+        if start == 0 && cursor.eat("#!") {
+            cursor.skip_until("\n");
+            return Ok(Token::Code);
+        }
+
+        let word_start = cursor.before().chars().next_back().is_none_or(ends_word);
+        if cursor.starts_with("#") && word_start {
+            cursor.skip(1);
+            let kind = match DIRECTIVES.iter().any(|d| cursor.rest().trim_start_matches(' ').starts_with(d)) {
+                true => CommentKind::Directive,
+                false => CommentKind::Line,
+            };
+            return Ok(cursor.line_comment(kind, 1));
+        }
+        if cursor.eat("((") {
+            self.arithmetic += 1;
+            return Ok(Token::Code);
+        }
+        if self.arithmetic > 0 && cursor.eat("))") {
+            self.arithmetic -= 1;
+            return Ok(Token::Code);
+        }
+        if cursor.eat("<<<") {
+            return Ok(Token::Code);
+        }
+        if self.arithmetic == 0 && cursor.starts_with("<<") {
+            if let Some((word, strip_tabs)) = heredoc(cursor) {
+                self.heredocs.push((word, if strip_tabs { TABS } else { &[] }));
+                return Ok(Token::Code);
+            }
+        }
+
+        match cursor.bump() {
+            Some('\\') => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+            Some('\'') => cursor.quoted("'", None, start),
+            Some('"') => double_quoted(cursor, start),
+            Some('$') => match cursor.peek() {
+                Some('\'') => {
+                    cursor.bump();
+                    cursor.quoted("'", Some('\\'), start)
+                }
+                Some('"') => {
+                    cursor.bump();
+                    double_quoted(cursor, start)
+                }
+                // `$#` is the number of arguments
+                Some('#') => {
+                    cursor.bump();
+                    Ok(Token::Code)
+                }
+                _ => Ok(Token::Code),
+            },
+            _ => Ok(Token::Code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomments::Type;
+
+    fn strip(src: &str) -> String {
+        crate::strip_str(src, Type::Shell).unwrap()
+    }
+
+    #[test]
+    fn substitution_in_double_quotes() {
+        assert_eq!(strip("x=\"$(echo \"a # b\")\" # c\n"), "x=\"$(echo \"a # b\")\" \n");
+        assert_eq!(strip("x=\"`echo \\\"a # b\\\"`\" # c\n"), "x=\"`echo \\\"a # b\\\"`\" \n");
+        assert_eq!(strip("x=\"$(f '\")' \"$((1 + 2))\")\" # c\n"), "x=\"$(f '\")' \"$((1 + 2))\")\" \n");
+    }
+
+    #[test]
+    fn word_start() {
+        assert_eq!(strip("echo a#b $# # c\n"), "echo a#b $# \n");
+        assert_eq!(strip("f() {# c\n:;# d\n}"), "f() {# c\n:;\n}");
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(strip("echo '#' \"# $x\" \\# # c\n"), "echo '#' \"# $x\" \\# \n");
+        assert!(crate::strip_str("echo 'open\n", Type::Shell).is_err());
+    }
+
+    #[test]
+    fn heredocs() {
+        let src = "cat <<EOF # c\n# kept\nEOF\ncat <<-'END'\n\t# kept\n\tEND\n# d\n";
+        assert_eq!(strip(src), "cat <<EOF \n# kept\nEOF\ncat <<-'END'\n\t# kept\n\tEND\n\n");
+        assert_eq!(strip("echo $((1 << 2)) # c\n"), "echo $((1 << 2)) \n");
+    }
+
+    #[test]
+    fn directives() {
+        let src = "#!/bin/sh\n# shellcheck disable=SC2086\n# c\n";
+        assert_eq!(strip(src), "#!/bin/sh\n# shellcheck disable=SC2086\n\n");
+    }
+}
@@ -0,0 +1,197 @@
+use super::rust::{is_ident_continue, is_ident_start};
+use crate::lexer::{expand, CommentKind, Cursor, Fault, Scan, Token};
+
+/// SQL dialect, which decides how comments, strings and quoted identifiers are written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    /// What most dialects share: `--` and `/* */` comments, `'strings'` and `"identifiers"`.
+    Standard,
+    /// Nested block comments, `E'...'` escape strings and `$tag$ ... $tag$` dollar quoting.
+    Postgres,
+    /// `#` comments, backslash escapes and `` `identifiers` ``. Its `/*! ... */` executable comments are
+    /// kept in every dialect.
+    MySql,
+    /// `` `identifiers` `` and `[identifiers]`.
+    Sqlite,
+    /// Nested block comments and `[identifiers]`.
+    TSql,
+}
+
+impl Dialect {
+    /// Parses the name of a dialect: `standard`, `postgres`, `mysql`, `sqlite` or `tsql`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Dialect::Standard),
+            "postgres" => Some(Dialect::Postgres),
+            "mysql" => Some(Dialect::MySql),
+            "sqlite" => Some(Dialect::Sqlite),
+            "tsql" => Some(Dialect::TSql),
+            _ => None,
+        }
+    }
+}
+
+// Scanner for SQL in one of its dialects.
+pub(crate) struct Sql {
+    dialect: Dialect,
+}
+
+impl Sql {
+    pub(crate) fn new(dialect: Dialect) -> Self {
+        Self { dialect }
+    }
+
+    // Finishes a PostgreSQL dollar-quoted string such as `$fn$ ... $fn$` at the cursor, or returns
+    // None if there is none, like at the parameter `$1`.
+    fn dollar_quoted(cursor: &mut Cursor<'_>, start: usize) -> Option<Result<Token, Fault>> {
+        if cursor.peek_nth(1).is_some_and(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let tag = cursor.eat_delimiter("${tag}$")?;
+        let close = expand("${tag}$", tag);
+        if cursor.skip_until(&close) {
+            cursor.skip(close.len());
+            return Some(Ok(Token::Str));
+        }
+        match cursor.recovering() {
+            true => Some(Ok(Token::Str)),
+            false => Some(Err(Fault::UnterminatedString { at: start })),
+        }
+    }
+}
+
+impl Scan for Sql {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        let dialect = self.dialect;
+
+        // MySQL requires a blank after `--`, so that `5--3` is a subtraction
+        let dashes = match dialect {
+            Dialect::MySql => cursor.rest().get(2..).is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)),
+            _ => true,
+        };
+        if dashes && cursor.eat("--") {
+            return Ok(cursor.line_comment(CommentKind::Line, 2));
+        }
+        if dialect == Dialect::MySql && cursor.eat("#") {
+            return Ok(cursor.line_comment(CommentKind::Line, 1));
+        }
+        // MySQL runs the contents of `/*! ... */`, so it is code, even in dumps read with another dialect
+        if cursor.starts_with("/*!") {
+            if cursor.skip_until("*/") {
+                cursor.skip(2);
+                return Ok(Token::Code);
+            }
+            if !cursor.recovering() {
+                return Err(Fault::UnterminatedComment { open: "/*!".into(), at: start });
+            }
+            return Ok(Token::Code);
+        }
+        if cursor.eat("/*") {
+            // optimizer hints
+            let kind = match cursor.starts_with("+") {
+                true => CommentKind::Directive,
+                false => CommentKind::Block,
+            };
+            let nests = matches!(dialect, Dialect::Postgres | Dialect::TSql);
+            return cursor.block_comment(kind, "/*", "*/", nests, start);
+        }
+        if let Some(token) = cursor.unmatched_close("/*", "*/") {
+            return token;
+        }
+
+        // MySQL strings use backslash escapes, and double quotes delimit strings rather than identifiers
+        let escape = match dialect {
+            Dialect::MySql => Some('\\'),
+            _ => None,
+        };
+        match cursor.peek() {
+            Some(c) if is_ident_start(c) => {
+                let word = cursor.eat_while(|c| is_ident_continue(c) || c == '$');
+                if dialect == Dialect::Postgres && word.eq_ignore_ascii_case("e") && cursor.eat("'") {
+                    return cursor.quoted("'", Some('\\'), start);
+                }
+                Ok(Token::Code)
+            }
+            Some('$') if dialect == Dialect::Postgres => Self::dollar_quoted(cursor, start).unwrap_or_else(|| {
+                cursor.bump();
+                Ok(Token::Code)
+            }),
+            Some('\'') => {
+                cursor.bump();
+                cursor.quoted("'", escape, start)
+            }
+            Some('"') => {
+                cursor.bump();
+                cursor.quoted("\"", escape, start)
+            }
+            Some('`') if matches!(dialect, Dialect::MySql | Dialect::Sqlite) => {
+                cursor.bump();
+                cursor.quoted("`", None, start)
+            }
+            Some('[') if matches!(dialect, Dialect::TSql | Dialect::Sqlite) => {
+                cursor.bump();
+                cursor.quoted("]", None, start)
+            }
+            _ => {
+                cursor.bump();
+                Ok(Token::Code)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dialect;
+    use crate::decomments::Type;
+    use crate::{Registry, Stripper};
+    use std::path::Path;
+
+    fn strip(dialect: Dialect, src: &str) -> String {
+        Stripper::new(Type::Sql(dialect)).strip_str(src).unwrap()
+    }
+
+    #[test]
+    fn standard() {
+        let src = "SELECT '--' AS \"/*\", 1 -- c\nFROM t /* d */ /*+ INDEX(t) */;\n";
+        assert_eq!(strip(Dialect::Standard, src), "SELECT '--' AS \"/*\", 1 \nFROM t  /*+ INDEX(t) */;\n");
+        assert_eq!(strip(Dialect::Standard, "SELECT 'it''s' -- c\n"), "SELECT 'it''s' \n");
+    }
+
+    #[test]
+    fn postgres() {
+        let src = "/* a /* b */ c */SELECT E'\\'--', $f$ -- $f$, $$/*$$, $1 -- d\n";
+        assert_eq!(strip(Dialect::Postgres, src), "SELECT E'\\'--', $f$ -- $f$, $$/*$$, $1 \n");
+        assert!(Stripper::new(Type::Sql(Dialect::Postgres)).strip_str("SELECT $a$ open").is_err());
+    }
+
+    #[test]
+    fn mysql() {
+        let src = "SELECT 5--3, \"a\\\"#\", `b#` # c\n/*!40101 SET x=1 */-- d\n";
+        assert_eq!(strip(Dialect::MySql, src), "SELECT 5--3, \"a\\\"#\", `b#` \n/*!40101 SET x=1 */\n");
+    }
+
+    #[test]
+    fn brackets() {
+        assert_eq!(strip(Dialect::TSql, "SELECT [a--b] /* x /* y */ z */-- c\n"), "SELECT [a--b] \n");
+        assert_eq!(strip(Dialect::Sqlite, "SELECT [a--b], `c--d` -- e\n"), "SELECT [a--b], `c--d` \n");
+    }
+
+    #[test]
+    fn dialects() {
+        assert_eq!(Dialect::parse("tsql"), Some(Dialect::TSql));
+        assert_eq!(Dialect::parse("oracle"), None);
+    }
+
+    #[test]
+    fn executable_comments() {
+        let src = "/*!40101 SET x=1 */;/* c */\n";
+        for dialect in [Dialect::Standard, Dialect::Postgres, Dialect::MySql, Dialect::Sqlite, Dialect::TSql] {
+            assert_eq!(strip(dialect, src), "/*!40101 SET x=1 */;\n");
+        }
+        let sql = Registry::default().detect(Path::new("dump.sql")).unwrap();
+        let stripper = Stripper::shared(sql).keep_directives(false).keep_legal(false);
+        assert_eq!(stripper.strip_str(src).unwrap(), "/*!40101 SET x=1 */;\n");
+    }
+}
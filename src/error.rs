@@ -0,0 +1,127 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where in the input a problem was found; lines and columns are 1-based.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    // Computes the location of the byte `offset` of `src`.
+    pub(crate) fn of(src: &str, offset: usize) -> Self {
+        let before = &src[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            file: None,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Errors reported by the lexer and the stripping API.
+#[derive(Debug)]
+pub enum Error {
+    UnmatchedClose { close: String, open: String, at: Location },
+    UnterminatedComment { open: String, at: Location },
+    UnterminatedString { at: Location },
+    Io { file: Option<PathBuf>, source: io::Error },
+    Decode { at: Location },
+    Verification { file: Option<PathBuf>, message: String },
+    Config { file: Option<PathBuf>, message: String },
+}
+
+impl Error {
+    /// Attaches the path of the file being processed to the error.
+    pub fn in_file(mut self, path: impl AsRef<Path>) -> Self {
+        let path = Some(path.as_ref().to_path_buf());
+        match &mut self {
+            Error::UnmatchedClose { at, .. }
+            | Error::UnterminatedComment { at, .. }
+            | Error::UnterminatedString { at }
+            | Error::Decode { at } => at.file = path,
+            Error::Io { file, .. } | Error::Verification { file, .. } | Error::Config { file, .. } => *file = path,
+        }
+        self
+    }
+
+    /// The position the error refers to, if it concerns a particular place in the input.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::UnmatchedClose { at, .. }
+            | Error::UnterminatedComment { at, .. }
+            | Error::UnterminatedString { at }
+            | Error::Decode { at } => Some(at),
+            Error::Io { .. } | Error::Verification { .. } | Error::Config { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnmatchedClose { close, open, at } => {
+                write!(f, "{}: found \"{}\" without a matching \"{}\"", at, close, open)
+            }
+            Error::UnterminatedComment { open, at } => {
+                write!(f, "{}: comment opened by \"{}\" is never closed", at, open)
+            }
+            Error::UnterminatedString { at } => write!(f, "{}: string literal is never closed", at),
+            Error::Io { file: Some(file), source } => write!(f, "{}: {}", file.display(), source),
+            Error::Io { file: None, source } => write!(f, "I/O error: {}", source),
+            Error::Decode { at } => write!(f, "{}: input is not valid UTF-8", at),
+            Error::Verification { file: Some(file), message } => write!(f, "{}: {}", file.display(), message),
+            Error::Verification { file: None, message } => f.write_str(message),
+            Error::Config { file: Some(file), message } => write!(f, "{}: invalid configuration: {}", file.display(), message),
+            Error::Config { file: None, message } => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Error::Io { file: None, source }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Location};
+
+    #[test]
+    fn locations() {
+        let at = Location::of("ab\ncdé\nf", 7);
+        assert_eq!((at.line, at.column), (2, 4));
+        assert_eq!(at.to_string(), "2:4");
+        assert_eq!(Location::of("", 0).to_string(), "1:1");
+    }
+
+    #[test]
+    fn file_paths() {
+        let error = Error::UnterminatedString { at: Location::of("x = \"", 4) }.in_file("src/a.rs");
+        assert_eq!(error.to_string(), "src/a.rs:1:5: string literal is never closed");
+        assert_eq!(error.location().and_then(|at| at.file.as_deref()), Some("src/a.rs".as_ref()));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::decomments::{Comment, Quote, Type, QUOTES};
+use crate::lexer::{Scan, Table};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Lexical rules of a language, and how to recognise its files.
+///
+/// Languages whose comments and strings are simple delimited runs only need to describe them in
+/// [`Language::comments`] and [`Language::quotes`]; others take over lexing with [`Language::scanner`].
+pub trait Language: fmt::Debug + Send + Sync {
+    /// Name shown to users, such as `C++`.
+    fn name(&self) -> &str;
+
+    /// Comment rules, tried in order before the string rules. A language that takes over lexing with
+    /// [`Language::scanner`] may have none.
+    fn comments(&self) -> Box<[Comment]>;
+
+    /// String literal rules, tried in order. Defaults to single and double quotes with backslash
+    /// escapes, and opaque triple back quote fences.
+    fn quotes(&self) -> Box<[Quote]> {
+        QUOTES.to_vec().into_boxed_slice()
+    }
+
+    /// Creates the scanner that lexes one input. The default one follows [`Language::comments`]
+    /// and [`Language::quotes`].
+    fn scanner(&self) -> Box<dyn Scan> {
+        Box::new(Table::new(self.comments(), self.quotes()))
+    }
+
+    /// Whether the file at `path` is written in this language, judging by its name.
+    fn matches(&self, path: &Path) -> bool;
+
+    /// Whether the content of the file at `path` shows that it is written in this language although
+    /// its name is ambiguous or says otherwise, like a `.h` header using C++ classes. Takes precedence
+    /// over [`Language::matches`].
+    fn sniff(&self, _path: &Path, _content: &str) -> bool {
+        false
+    }
+
+    /// Whether this language is called `name` by a shebang interpreter or an editor modeline.
+    /// `name` is in lower case.
+    fn is_named(&self, name: &str) -> bool {
+        self.name().eq_ignore_ascii_case(name)
+    }
+
+    /// Checks that stripping `input` into `output` did not break a document that was valid
+    /// before, describing the problem otherwise. Passes by default.
+    fn verify(&self, _input: &str, _output: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Languages that files are matched against. The default registry holds the built-in languages.
+///
+/// Languages registered later take precedence, so that they can replace a built-in one.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    languages: Vec<Arc<dyn Language>>,
+}
+
+impl Registry {
+    /// Creates a registry without any language.
+    pub fn empty() -> Self {
+        Self { languages: Vec::new() }
+    }
+
+    /// Adds `lang`, which takes precedence over the languages already registered.
+    pub fn register(&mut self, lang: impl Language + 'static) -> &mut Self {
+        self.register_shared(Arc::new(lang))
+    }
+
+    /// Adds a language that is shared with other owners.
+    pub fn register_shared(&mut self, lang: Arc<dyn Language>) -> &mut Self {
+        self.languages.push(lang);
+        self
+    }
+
+    /// Returns the language of the file at `path`.
+    pub fn detect(&self, path: &Path) -> Option<Arc<dyn Language>> {
+        self.languages.iter().rev().find(|lang| lang.matches(path)).cloned()
+    }
+
+    /// Returns the language called `name`, ignoring case and a trailing version number as in `python3.12`.
+    pub fn find(&self, name: &str) -> Option<Arc<dyn Language>> {
+        let name = name.to_lowercase();
+        let unversioned = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        [name.as_str(), unversioned]
+            .iter()
+            .filter(|name| !name.is_empty())
+            .find_map(|name| self.languages.iter().rev().find(|lang| lang.is_named(name)))
+            .cloned()
+    }
+
+    /// Returns the language of the file at `path` whose text is `content`, and why it was chosen.
+    ///
+    /// An Emacs or Vim modeline comes first, then the content and the name of the file, and last the
+    /// interpreter named on its shebang line.
+    pub fn identify(&self, path: &Path, content: &str) -> Option<(Arc<dyn Language>, Reason)> {
+        let by_modeline = || modeline(content).and_then(|mode| Some((self.find(&mode)?, Reason::Modeline(mode))));
+        let by_content = || {
+            let lang = self.languages.iter().rev().find(|lang| lang.sniff(path, content))?;
+            Some((lang.clone(), Reason::Content))
+        };
+        let by_name = || self.detect(path).map(|lang| (lang, Reason::Name));
+        let by_shebang = || {
+            let interpreter = shebang(content)?;
+            Some((self.find(&interpreter)?, Reason::Shebang(interpreter)))
+        };
+        by_modeline().or_else(by_content).or_else(by_name).or_else(by_shebang)
+    }
+
+    /// The registered languages, from the highest precedence to the lowest.
+    pub fn languages(&self) -> impl Iterator<Item = &Arc<dyn Language>> {
+        self.languages.iter().rev()
+    }
+}
+
+/// Why [`Registry::identify`] chose the language of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// An Emacs or Vim modeline set this mode.
+    Modeline(String),
+    /// The content of the file gave it away.
+    Content,
+    /// The name or extension of the file.
+    Name,
+    /// Its shebang line runs this interpreter.
+    Shebang(String),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Modeline(mode) => write!(f, "modeline sets \"{}\"", mode),
+            Reason::Content => f.write_str("content"),
+            Reason::Name => f.write_str("file name"),
+            Reason::Shebang(interpreter) => write!(f, "shebang runs \"{}\"", interpreter),
+        }
+    }
+}
+
+// Returns the program run by a `#!` line, looking through `env` and its options.
+fn shebang(content: &str) -> Option<String> {
+    let line = content.strip_prefix("#!")?.lines().next()?;
+    let mut words = line.split_whitespace().map(|word| word.rsplit('/').next().unwrap_or(word));
+    let mut program = words.next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    Some(program.to_string())
+}
+
+// Returns the mode set by an Emacs modeline (`-*- mode: python -*-` on one of the first two lines)
+// or a Vim modeline (`vim: set ft=ruby:` on one of the first or last five lines), in lower case.
+fn modeline(content: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let emacs = lines.iter().take(2).find_map(|line| {
+        let (_, rest) = line.split_once("-*-")?;
+        let (vars, _) = rest.split_once("-*-")?;
+        match vars.contains(':') {
+            true => vars.split(';').find_map(|var| {
+                let (name, value) = var.split_once(':')?;
+                (name.trim().eq_ignore_ascii_case("mode")).then(|| value.trim())
+            }),
+            false => Some(vars.trim()),
+        }
+    });
+    let tail = lines.len().saturating_sub(5).max(5);
+    let vim = || {
+        lines.iter().take(5).chain(lines.iter().skip(tail)).find_map(|line| {
+            let at = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+                let i = line.find(marker)?;
+                (i == 0 || line[..i].ends_with(char::is_whitespace)).then(|| i + marker.len())
+            })?;
+            line[at..].split(|c: char| c.is_whitespace() || c == ':').find_map(|option| {
+                let (name, value) = option.split_once('=')?;
+                ["ft", "filetype", "syn", "syntax"].contains(&name).then_some(value)
+            })
+        })
+    };
+    emacs.or_else(vim).filter(|mode| !mode.is_empty()).map(str::to_lowercase)
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        for lang in Type::ALL {
+            registry.register(lang);
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Language, Reason, Registry};
+    use crate::decomments::{Comment, Type};
+    use crate::Stripper;
+    use std::borrow::Cow;
+    use std::path::Path;
+
+    // A language with `--` line comments, as a downstream crate would declare it.
+    #[derive(Debug)]
+    struct Dashes;
+
+    impl Language for Dashes {
+        fn name(&self) -> &str {
+            "Dashes"
+        }
+
+        fn comments(&self) -> Box<[Comment]> {
+            Box::new([Comment {
+                open_pat: Cow::Borrowed("--"),
+                close_pat: Cow::Borrowed("\n"),
+                nests: false,
+                keep_close_pat: true,
+                allow_close_pat: true,
+                doc: false,
+            }])
+        }
+
+        fn matches(&self, path: &Path) -> bool {
+            path.extension().is_some_and(|e| e == "rs" || e == "dash")
+        }
+    }
+
+    #[test]
+    fn builtin_languages() {
+        let registry = Registry::default();
+        assert_eq!(registry.detect(Path::new("src/main.rs")).unwrap().name(), "Rust");
+        assert_eq!(registry.detect(Path::new("a.hpp")).unwrap().name(), "C++");
+        assert!(registry.detect(Path::new("README")).is_none());
+        assert_eq!(registry.find("c#").unwrap().name(), "C#");
+        assert!(Registry::empty().detect(Path::new("main.rs")).is_none());
+    }
+
+    #[test]
+    fn later_languages_take_precedence() {
+        let mut registry = Registry::default();
+        registry.register(Dashes);
+        assert_eq!(registry.detect(Path::new("main.rs")).unwrap().name(), "Dashes");
+        assert_eq!(registry.languages().next().unwrap().name(), "Dashes");
+        let stripper = Stripper::shared(registry.find("dashes").unwrap());
+        assert_eq!(stripper.strip_str("a \"--\" -- c\nb").unwrap(), "a \"--\" \nb");
+        assert_eq!(Stripper::new(Type::Rust).strip_str("a // c\n").unwrap(), "a \n");
+    }
+
+    #[test]
+    fn modelines() {
+        let registry = Registry::default();
+        let identify = |path: &str, content: &str| registry.identify(Path::new(path), content).map(|(lang, why)| (lang.name().to_string(), why));
+        assert_eq!(identify("a.txt", "# -*- mode: python; coding: utf-8 -*-\n"), Some(("Python".into(), Reason::Modeline("python".into()))));
+        assert_eq!(identify("a.txt", "// -*- C++ -*-\n"), Some(("C++".into(), Reason::Modeline("c++".into()))));
+        assert_eq!(identify("a.rs", "x\n\n\n\n\n\n\n// vim: set ft=go:\n"), Some(("Go".into(), Reason::Modeline("go".into()))));
+        // a modeline naming an unknown mode is ignored
+        assert_eq!(identify("a.rs", "// -*- mode: fundamental -*-\n"), Some(("Rust".into(), Reason::Name)));
+    }
+
+    #[test]
+    fn shebangs() {
+        let registry = Registry::default();
+        let (lang, why) = registry.identify(Path::new("bin/run"), "#!/usr/bin/env -S python3.12 -u\nprint()\n").unwrap();
+        assert_eq!((lang.name(), why), ("Python", Reason::Shebang("python3.12".into())));
+        assert!(registry.identify(Path::new("bin/run"), "#!/bin/unknown\n").is_none());
+        assert_eq!(registry.identify(Path::new("a.java"), "#!/usr/bin/python\n").unwrap().1, Reason::Name);
+    }
+
+    #[test]
+    fn content() {
+        let registry = Registry::default();
+        let name = |path: &str, content: &str| registry.identify(Path::new(path), content).unwrap().0.name().to_string();
+        assert_eq!(name("a.h", "namespace a {\n}\n"), "C++");
+        assert_eq!(name("a.h", "#include <vector>\nstd::vector<int> v;\n"), "C++");
+        assert_eq!(name("a.h", "int f(void);\n"), "C");
+        assert_eq!(name("a.h", "template <typename T> T f(T);\n"), "C++");
+        assert_eq!(name("a.h", "template_t *f(void);\n"), "C");
+        assert_eq!(name("a.ts", "<?xml version=\"1.0\"?>\n<TS/>\n"), "XML");
+        assert_eq!(name("a.ts", "let a = 1;\n"), "TypeScript");
+        assert_eq!(name("src/Makefile", ""), "Make");
+        assert_eq!(name("Dockerfile.dev", ""), "Dockerfile");
+        // SaltStack states are YAML
+        assert!(registry.identify(Path::new("top.sls"), "base:\n").is_none());
+    }
+}
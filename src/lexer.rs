@@ -0,0 +1,591 @@
+use crate::decomments::{Comment, Quote, QUOTES};
+use crate::language::Language;
+use crate::error::{Error, Location};
+use std::borrow::Cow;
+
+/// Byte range of a lexeme together with the line and column (both 1-based) where it starts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Broad classification of a comment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CommentKind {
+    Line,
+    Block,
+    Doc,
+    /// License or copyright notice meant to survive minification, such as `/*! ... */`.
+    Legal,
+    /// Comment read by a compiler or tool, such as `//go:build`; removing it changes behaviour.
+    Directive,
+}
+
+/// A lexeme produced by [`Lexer`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// Source text that is neither a comment nor a string literal.
+    Code(Span),
+    /// A comment; `delimiter` is its opening pattern and `text` its body without the delimiters.
+    Comment {
+        kind: CommentKind,
+        delimiter: &'a str,
+        span: Span,
+        text: &'a str,
+    },
+    /// A string literal, quotes included.
+    StringLiteral(Span),
+}
+
+impl Event<'_> {
+    pub fn span(&self) -> Span {
+        match *self {
+            Event::Code(span) | Event::StringLiteral(span) | Event::Comment { span, .. } => span,
+        }
+    }
+}
+
+// Placeholder of a delimiter pattern, standing for text captured when the opening delimiter is
+// matched and repeated by the closing one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Capture {
+    Run(char), // `{=}`: a run of `=`, possibly empty
+    Tag,       // `{tag}`: a word, possibly empty
+}
+
+// Splits a delimiter pattern around its placeholder, if it has one.
+fn placeholder(pat: &str) -> Option<(&str, Capture, &str)> {
+    let open = pat.find('{')?;
+    let len = pat[open..].find('}')?;
+    let capture = match &pat[open + 1..open + len] {
+        "tag" => Capture::Tag,
+        run if run.chars().count() == 1 => Capture::Run(run.chars().next()?),
+        _ => return None,
+    };
+    Some((&pat[..open], capture, &pat[open + len + 1..]))
+}
+
+// Replaces the placeholder of `pat` with the `captured` text.
+pub(crate) fn expand(pat: &str, captured: &str) -> Cow<'static, str> {
+    match placeholder(pat) {
+        Some((before, _, after)) => format!("{}{}{}", before, captured, after).into(),
+        None => pat.to_string().into(),
+    }
+}
+
+/// Cursor over the source text that the scanning routines advance.
+pub struct Cursor<'a> {
+    full: &'a str,
+    src: &'a str, // the part of `full` that may be scanned
+    pos: usize,
+    pub(crate) recover: bool, // whether malformed input is tolerated rather than reported
+}
+
+impl<'a> Cursor<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { full: src, src, pos: 0, recover: false }
+    }
+
+    /// Runs `f` with the input cut off at `end`, so that nothing past it can be scanned.
+    pub fn within<T>(&mut self, end: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        let src = self.src;
+        self.src = &self.full[..end.min(src.len())];
+        let result = f(self);
+        self.src = src;
+        result
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Whether malformed input should be tolerated rather than reported.
+    pub fn recovering(&self) -> bool {
+        self.recover
+    }
+
+    /// Already scanned input.
+    pub fn before(&self) -> &'a str {
+        &self.src[..self.pos]
+    }
+
+    /// Whether the cursor is at the very beginning of a line.
+    pub fn at_line_begin(&self) -> bool {
+        self.pos == 0 || self.before().ends_with('\n')
+    }
+
+    /// Whether only blanks precede the cursor on its line.
+    pub fn at_line_start(&self) -> bool {
+        let before = self.before();
+        before[before.rfind('\n').map_or(0, |i| i + 1)..].chars().all(|c| c == ' ' || c == '\t')
+    }
+
+    /// Remaining, not yet scanned input.
+    pub fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.src.len()
+    }
+
+    pub fn starts_with(&self, pat: &str) -> bool {
+        !pat.is_empty() && self.rest().starts_with(pat)
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Returns the character `n` positions ahead of the cursor.
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    /// Advances by one character and returns it.
+    pub fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Moves back to a position returned by `pos`.
+    pub fn rewind(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Advances by `n` bytes, which must end on a character boundary.
+    pub fn skip(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    /// Advances past `pat` if the input starts with it.
+    pub fn eat(&mut self, pat: &str) -> bool {
+        let matched = self.starts_with(pat);
+        if matched {
+            self.skip(pat.len());
+        }
+        matched
+    }
+
+    /// Advances past a delimiter matching the pattern `pat` and returns the text captured by its
+    /// placeholder, which is empty if it has none.
+    ///
+    /// A placeholder is `{=}` (or any other single character between braces) for a run of that
+    /// character, or `{tag}` for a word, both possibly empty. The closing delimiter repeats the
+    /// captured text, like the level of Lua's `[==[ ... ]==]` or the tag of `$body$ ... $body$`.
+    pub fn eat_delimiter(&mut self, pat: &str) -> Option<&'a str> {
+        let Some((before, capture, after)) = placeholder(pat) else {
+            return self.eat(pat).then_some("");
+        };
+        let start = self.pos;
+        if before.is_empty() || self.eat(before) {
+            let captured = match capture {
+                Capture::Run(run) => self.eat_while(|c| c == run),
+                Capture::Tag => self.eat_while(|c| c.is_alphanumeric() || c == '_'),
+            };
+            if after.is_empty() || self.eat(after) {
+                return Some(captured);
+            }
+        }
+        self.rewind(start);
+        None
+    }
+
+    /// Advances while `pred` holds and returns the skipped text.
+    pub fn eat_while(&mut self, mut pred: impl FnMut(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&mut pred) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Moves to the end of the input.
+    pub fn skip_to_end(&mut self) {
+        self.pos = self.src.len();
+    }
+
+    /// Moves to the next occurrence of `pat` without consuming it; returns false at the end of input.
+    pub fn skip_until(&mut self, pat: &str) -> bool {
+        match self.rest().find(pat) {
+            Some(i) => {
+                self.skip(i);
+                true
+            }
+            None => {
+                self.skip_to_end();
+                false
+            }
+        }
+    }
+
+    /// Finishes a line comment that starts at the cursor's current line, stopping before the newline.
+    pub fn line_comment(&mut self, kind: CommentKind, open: usize) -> Token {
+        self.skip_until("\n");
+        Token::Comment { kind, open, close: 0 }
+    }
+
+    /// Finishes a block comment whose opening delimiter, starting at `start`, has been consumed.
+    /// Everything consumed so far is reported as the delimiter; `open` is what nested comments start with.
+    pub fn block_comment(
+        &mut self,
+        kind: CommentKind,
+        open: &str,
+        close: &str,
+        nests: bool,
+        start: usize,
+    ) -> Result<Token, Fault> {
+        let open_len = self.pos - start;
+        let mut depth = 0;
+        loop {
+            if self.eat(close) {
+                if depth == 0 {
+                    return Ok(Token::Comment { kind, open: open_len, close: close.len() });
+                }
+                depth -= 1;
+            } else if nests && self.eat(open) {
+                depth += 1;
+            } else if self.bump().is_none() {
+                if self.recover {
+                    return Ok(Token::Comment { kind, open: open_len, close: 0 });
+                }
+                return Err(Fault::UnterminatedComment { open: open.to_string().into(), at: start });
+            }
+        }
+    }
+
+    /// Advances past the `*` that makes the block comment whose `/*` has just been consumed a
+    /// `/** ... */` documentation comment; `/**/` and `/***` banners are not.
+    pub fn eat_doc_star(&mut self) -> bool {
+        let doc = self.starts_with("*") && !self.starts_with("*/") && !self.starts_with("**");
+        doc && self.eat("*")
+    }
+
+    /// Handles a `close` delimiter at the cursor that closes no `open` one: it is reported, or
+    /// skipped as code when recovering. Returns `None` if the input does not start with `close`.
+    pub fn unmatched_close(&mut self, open: &str, close: &str) -> Option<Result<Token, Fault>> {
+        if !self.starts_with(close) {
+            return None;
+        }
+        if !self.recover {
+            let (close, open) = (close.to_string().into(), open.to_string().into());
+            return Some(Err(Fault::UnmatchedClose { close, open, at: self.pos }));
+        }
+        self.skip(close.len());
+        Some(Ok(Token::Code))
+    }
+
+    /// Finishes a string literal whose opening quote, starting at `start`, has been consumed.
+    /// `escape` is the character that makes the following one literal, if the string has one.
+    pub fn quoted(&mut self, quote: &str, escape: Option<char>, start: usize) -> Result<Token, Fault> {
+        loop {
+            if self.eat(quote) {
+                return Ok(Token::Str);
+            }
+            match self.bump() {
+                Some(c) if Some(c) == escape => {
+                    self.bump();
+                }
+                Some(_) => {}
+                None if self.recover => return Ok(Token::Str),
+                None => return Err(Fault::UnterminatedString { at: start }),
+            }
+        }
+    }
+}
+
+/// What a scanning routine recognised at the cursor.
+pub enum Token {
+    Code,
+    /// `open` and `close` are the byte lengths of the delimiters included in the lexeme.
+    Comment { kind: CommentKind, open: usize, close: usize },
+    Str,
+}
+
+/// Malformed input found while scanning; `at` is the byte offset it refers to.
+pub enum Fault {
+    UnmatchedClose { close: Cow<'static, str>, open: Cow<'static, str>, at: usize },
+    UnterminatedComment { open: Cow<'static, str>, at: usize },
+    UnterminatedString { at: usize },
+}
+
+/// Language-specific scanning: recognises one lexeme at the cursor and advances past it.
+/// A scanner is created for each input, so it may keep state between calls.
+pub trait Scan {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault>;
+}
+
+/// Scanner driven by tables of comment patterns and string delimiters.
+pub struct Table {
+    comments: Box<[Comment]>,
+    quotes: Box<[Quote]>,
+}
+
+impl Table {
+    pub fn new(comments: Box<[Comment]>, quotes: Box<[Quote]>) -> Self {
+        Self { comments, quotes }
+    }
+}
+
+impl Scan for Table {
+    fn scan(&mut self, cursor: &mut Cursor<'_>) -> Result<Token, Fault> {
+        let start = cursor.pos();
+        for comment in self.comments.iter() {
+            let Comment {
+                open_pat,
+                close_pat,
+                nests,
+                keep_close_pat,
+                allow_close_pat,
+                ..
+            } = comment;
+
+            if let Some(captured) = cursor.eat_delimiter(open_pat) {
+                let close = expand(close_pat, captured);
+                if !keep_close_pat {
+                    let open = expand(open_pat, captured);
+                    return cursor.block_comment(comment.kind(), &open, &close, *nests, start);
+                }
+                let open = cursor.pos() - start;
+                cursor.skip_until(&close);
+                return Ok(Token::Comment { kind: comment.kind(), open, close: 0 });
+            } else if !allow_close_pat {
+                if let Some(token) = cursor.unmatched_close(open_pat, close_pat) {
+                    return token;
+                }
+            }
+        }
+
+        for Quote { open, close, escape } in self.quotes.iter() {
+            if let Some(captured) = cursor.eat_delimiter(open) {
+                return cursor.quoted(&expand(close, captured), *escape, start);
+            }
+        }
+
+        cursor.bump();
+        Ok(Token::Code)
+    }
+}
+
+/// Splits source text into [`Event`]s according to the lexical rules of a language.
+///
+/// Malformed input is reported as an [`Error`], after which the lexer stops, unless recovery
+/// is enabled with [`Lexer::recover`].
+pub struct Lexer<'a> {
+    cursor: Cursor<'a>,
+    scanner: Box<dyn Scan>,
+    line_starts: Vec<usize>,
+    lookahead: Option<Result<Event<'a>, Error>>,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer that recognises the given comment patterns and strings delimited by
+    /// single, double or triple back quotes.
+    pub fn new(src: &'a str, comments: Box<[Comment]>) -> Self {
+        Self::with_scanner(src, Box::new(Table::new(comments, QUOTES.to_vec().into_boxed_slice())))
+    }
+
+    /// Creates a lexer that follows the lexical rules of `lang`.
+    pub fn for_language(src: &'a str, lang: &dyn Language) -> Self {
+        Self::with_scanner(src, lang.scanner())
+    }
+
+    fn with_scanner(src: &'a str, scanner: Box<dyn Scan>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            cursor: Cursor::new(src),
+            scanner,
+            line_starts,
+            lookahead: None,
+        }
+    }
+
+    /// When enabled, stray closing delimiters are kept as code and unterminated comments and
+    /// strings run to the end of the input instead of failing.
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.cursor.recover = recover;
+        self
+    }
+
+    /// The text being lexed.
+    pub fn source(&self) -> &'a str {
+        self.cursor.full
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        let line = self.line_starts.partition_point(|&s| s <= start);
+        let line_start = self.line_starts[line - 1];
+        let column = self.cursor.full[line_start..start].chars().count() + 1;
+        Span { start, end, line, column }
+    }
+
+    fn location(&self, offset: usize) -> Location {
+        let Span { line, column, .. } = self.span(offset, offset);
+        Location { file: None, line, column }
+    }
+
+    fn error(&self, fault: Fault) -> Error {
+        match fault {
+            Fault::UnmatchedClose { close, open, at } => Error::UnmatchedClose {
+                close: close.into_owned(),
+                open: open.into_owned(),
+                at: self.location(at),
+            },
+            Fault::UnterminatedComment { open, at } => Error::UnterminatedComment {
+                open: open.into_owned(),
+                at: self.location(at),
+            },
+            Fault::UnterminatedString { at } => Error::UnterminatedString { at: self.location(at) },
+        }
+    }
+
+    fn next_event(&mut self) -> Option<Result<Event<'a>, Error>> {
+        if let Some(event) = self.lookahead.take() {
+            return Some(event);
+        }
+
+        // Consecutive code lexemes are merged into a single event.
+        let code_start = self.cursor.pos;
+        while !self.cursor.is_empty() {
+            let start = self.cursor.pos;
+            let event = match self.scanner.scan(&mut self.cursor) {
+                Ok(Token::Code) => continue,
+                Ok(Token::Str) => Ok(Event::StringLiteral(self.span(start, self.cursor.pos))),
+                Ok(Token::Comment { kind, open, close }) => {
+                    let end = self.cursor.pos;
+                    Ok(Event::Comment {
+                        kind,
+                        delimiter: &self.cursor.full[start..start + open],
+                        span: self.span(start, end),
+                        text: &self.cursor.full[start + open..end - close],
+                    })
+                }
+                Err(fault) => {
+                    // nothing after malformed input is reported
+                    self.cursor.skip_to_end();
+                    Err(self.error(fault))
+                }
+            };
+            if start == code_start {
+                return Some(event);
+            }
+            self.lookahead = Some(event);
+            return Some(Ok(Event::Code(self.span(code_start, start))));
+        }
+
+        (self.cursor.pos > code_start).then(|| Ok(Event::Code(self.span(code_start, self.cursor.pos))))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, CommentKind, Cursor, Event, Span};
+    use crate::decomments::Type;
+    use crate::Stripper;
+
+    fn events(src: &str, lang: Type) -> Vec<Event<'_>> {
+        Stripper::new(lang).events(src).collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn positions() {
+        let src = "a\n  /* é */ \"s\" // c\n";
+        assert_eq!(
+            events(src, Type::Rust),
+            [
+                Event::Code(Span { start: 0, end: 4, line: 1, column: 1 }),
+                Event::Comment {
+                    kind: CommentKind::Block,
+                    delimiter: "/*",
+                    span: Span { start: 4, end: 12, line: 2, column: 3 },
+                    text: " é ",
+                },
+                Event::Code(Span { start: 12, end: 13, line: 2, column: 10 }),
+                Event::StringLiteral(Span { start: 13, end: 16, line: 2, column: 11 }),
+                Event::Code(Span { start: 16, end: 17, line: 2, column: 14 }),
+                Event::Comment {
+                    kind: CommentKind::Line,
+                    delimiter: "//",
+                    span: Span { start: 17, end: 21, line: 2, column: 15 },
+                    text: " c",
+                },
+                Event::Code(Span { start: 21, end: 22, line: 2, column: 19 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn doc_comments() {
+        let kinds: Vec<_> = events("/// a\n//! b\n/** c */ /**/ /*** d */", Type::Rust)
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Comment { kind, text, .. } => Some((kind, text)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kinds, [(CommentKind::Doc, " a"), (CommentKind::Doc, " b"), (CommentKind::Doc, " c "), (CommentKind::Block, ""), (CommentKind::Block, "** d ")]);
+    }
+
+    #[test]
+    fn spans_cover_the_input() {
+        let src = "x = 'a # b' # c\n{- {- d -} -} y";
+        let mut end = 0;
+        for event in events(src, Type::Python).into_iter().chain(events(src, Type::Haskell)) {
+            let span = event.span();
+            assert!(span.start == end || span.start == 0);
+            end = span.end;
+        }
+        assert_eq!(end, src.len());
+    }
+
+    #[test]
+    fn malformed_input() {
+        let strip = |src| Stripper::new(Type::Rust).strip_str(src).map_err(|e| e.to_string());
+        assert_eq!(strip("a\nb */"), Err("2:3: found \"*/\" without a matching \"/*\"".to_string()));
+        assert_eq!(strip("x /* y"), Err("1:3: comment opened by \"/*\" is never closed".to_string()));
+        assert_eq!(strip("x = \"y"), Err("1:5: string literal is never closed".to_string()));
+    }
+
+    #[test]
+    fn recovery() {
+        let stripper = Stripper::new(Type::Rust).recover(true);
+        assert_eq!(stripper.strip_str("a */ b // c").unwrap(), "a */ b ");
+        assert_eq!(stripper.strip_str("x /* y").unwrap(), "x ");
+        assert_eq!(stripper.strip_str("x = \"y // z").unwrap(), "x = \"y // z");
+    }
+
+    #[test]
+    fn captured_delimiters() {
+        let mut cursor = Cursor::new("[==[ a ]==]");
+        assert_eq!(cursor.eat_delimiter("[{=}["), Some("=="));
+        assert_eq!(expand("]{=}]", "=="), "]==]");
+        let mut cursor = Cursor::new("$1 $body$");
+        assert_eq!(cursor.eat_delimiter("${tag}$"), None);
+        assert_eq!(cursor.pos(), 0);
+        assert_eq!(cursor.eat_delimiter("$1"), Some(""));
+        cursor.skip(1);
+        assert_eq!(cursor.eat_delimiter("${tag}$"), Some("body"));
+    }
+
+    #[test]
+    fn lua_long_brackets() {
+        let strip = |src| Stripper::new(Type::Lua).strip_str(src).unwrap();
+        assert_eq!(strip("a = [==[ -- ]] ]==] -- c\n--[=[ d ]] ]=]b"), "a = [==[ -- ]] ]==] \nb");
+        assert_eq!(strip("t[t[1]] = '--' --- doc\n"), "t[t[1]] = '--' \n");
+        assert!(Stripper::new(Type::Lua).strip_str("--[[ open ]=]").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Removes comments from source code.
+//!
+//! The simplest entry point is [`strip_str`]; [`Stripper`] exposes the available options and
+//! can also stream from a reader into a writer.
+
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+mod config;
+mod decomments;
+mod error;
+mod language;
+mod lexer;
+mod verify;
+
+pub use crate::config::{Config, Syntax, CONFIG_FILES};
+pub use crate::decomments::{Comment, Dialect as SqlDialect, IntoWithoutComments, Quote, Type as Builtin, WithoutComments};
+pub use crate::error::{Error, Location};
+pub use crate::language::{Language, Reason, Registry};
+pub use crate::lexer::{CommentKind, Cursor, Event, Fault, Lexer, Scan, Span, Table, Token};
+
+/// Configurable comment stripper for a single language.
+#[derive(Clone, Debug)]
+pub struct Stripper {
+    lang: Arc<dyn Language>,
+    keep_docs: bool,
+    keep_legal: bool,
+    keep_directives: bool,
+    pass_docstrings: bool,
+    recover: bool,
+    verify: bool,
+}
+
+impl Stripper {
+    /// Creates a stripper that removes every comment of `lang` except license notices and
+    /// directives.
+    pub fn new(lang: impl Language + 'static) -> Self {
+        Self::shared(Arc::new(lang))
+    }
+
+    /// Creates a stripper for a language shared with other owners, such as one found in a [`Registry`].
+    pub fn shared(lang: Arc<dyn Language>) -> Self {
+        Self {
+            lang,
+            keep_docs: false,
+            keep_legal: true,
+            keep_directives: true,
+            pass_docstrings: false,
+            recover: false,
+            verify: false,
+        }
+    }
+
+    /// Keeps documentation comments (`///`, docstrings, Haddock annotations...) in the output.
+    pub fn keep_docs(mut self, keep: bool) -> Self {
+        self.keep_docs = keep;
+        self
+    }
+
+    /// Keeps license notices such as `/*! ... */` or comments tagged `@license`/`@preserve`
+    /// (the default).
+    pub fn keep_legal(mut self, keep: bool) -> Self {
+        self.keep_legal = keep;
+        self
+    }
+
+    /// Keeps comments that compilers or tools interpret, such as `//go:build` (the default).
+    pub fn keep_directives(mut self, keep: bool) -> Self {
+        self.keep_directives = keep;
+        self
+    }
+
+    /// Replaces each removed Python docstring with `pass`, so that a body which held nothing
+    /// but its docstring stays valid.
+    pub fn pass_docstrings(mut self, pass: bool) -> Self {
+        self.pass_docstrings = pass;
+        self
+    }
+
+    /// Recovers from malformed input (stray closing delimiters, unterminated comments or strings)
+    /// on a best-effort basis instead of returning an error.
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// Checks that the output is still a valid document (well-formed XML, YAML or TOML holding the
+    /// same data...) whenever the input was, failing with [`Error::Verification`] otherwise. Has no
+    /// effect for source code.
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Returns the language this stripper was created for.
+    pub fn language(&self) -> &dyn Language {
+        &*self.lang
+    }
+
+    /// Splits `src` into code, comment and string literal events.
+    pub fn events<'a>(&self, src: &'a str) -> Lexer<'a> {
+        Lexer::for_language(src, &*self.lang).recover(self.recover)
+    }
+
+    fn without_comments<'a>(&self, src: &'a str) -> WithoutComments<'a> {
+        WithoutComments::new(self.events(src))
+            .keep_docs(self.keep_docs)
+            .keep_legal(self.keep_legal)
+            .keep_directives(self.keep_directives)
+            .pass_docstrings(self.pass_docstrings)
+    }
+
+    /// Removes comments from `src`.
+    pub fn strip_str(&self, src: &str) -> Result<String, Error> {
+        let output = self.without_comments(src).collect::<Result<String, Error>>()?;
+        if self.verify {
+            self.lang.verify(src, &output).map_err(|message| Error::Verification { file: None, message })?;
+        }
+        Ok(output)
+    }
+
+    /// Reads the file at `path` and returns its contents without comments. Errors carry the path.
+    pub fn strip_file(&self, path: impl AsRef<Path>) -> Result<String, Error> {
+        let path = path.as_ref();
+        std::fs::read(path)
+            .map_err(Error::from)
+            .and_then(decode)
+            .and_then(|src| self.strip_str(&src))
+            .map_err(|e| e.in_file(path))
+    }
+
+    /// Reads all of `reader`, removes its comments and writes the result to `writer`.
+    ///
+    /// On error, part of the output may already have been written.
+    pub fn strip_reader<R: Read, W: Write>(&self, mut reader: R, mut writer: W) -> Result<(), Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let src = decode(bytes)?;
+
+        if self.verify {
+            // the whole output is needed before anything can be written
+            writer.write_all(self.strip_str(&src)?.as_bytes())?;
+            return Ok(writer.flush()?);
+        }
+
+        let mut chunk = String::new();
+        for c in self.without_comments(&src) {
+            chunk.push(c?);
+            if chunk.len() >= 8192 {
+                writer.write_all(chunk.as_bytes())?;
+                chunk.clear();
+            }
+        }
+        writer.write_all(chunk.as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Removes every comment of `lang` except license notices and directives from `src`.
+pub fn strip_str(src: &str, lang: impl Language + 'static) -> Result<String, Error> {
+    Stripper::new(lang).strip_str(src)
+}
+
+/// Removes every comment of `lang` except license notices and directives while copying `reader` into `writer`.
+pub fn strip_reader<R: Read, W: Write>(reader: R, writer: W, lang: impl Language + 'static) -> Result<(), Error> {
+    Stripper::new(lang).strip_reader(reader, writer)
+}
+
+// Converts raw input to text, locating the first invalid UTF-8 sequence on failure.
+fn decode(bytes: Vec<u8>) -> Result<String, Error> {
+    String::from_utf8(bytes).map_err(|e| {
+        let valid = e.utf8_error().valid_up_to();
+        let prefix = std::str::from_utf8(&e.as_bytes()[..valid]).unwrap();
+        Error::Decode { at: Location::of(prefix, valid) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_line_and_block_comments() {
+        assert_eq!(strip_str("let x = 1; // one\nlet y = /* two */ 2;\n", Builtin::Rust).unwrap(), "let x = 1; \nlet y =  2;\n");
+    }
+
+    #[test]
+    fn keeps_docs_on_request() {
+        let src = "/// Doc.\nfn f() {} // c\n";
+        assert_eq!(Stripper::new(Builtin::Rust).strip_str(src).unwrap(), "\nfn f() {} \n");
+        assert_eq!(Stripper::new(Builtin::Rust).keep_docs(true).strip_str(src).unwrap(), "/// Doc.\nfn f() {} \n");
+    }
+
+    #[test]
+    fn streams_from_reader() {
+        let mut out = Vec::new();
+        strip_reader("x = 1 # one\n".as_bytes(), &mut out, Builtin::Python).unwrap();
+        assert_eq!(out, b"x = 1 \n");
+        let invalid: &[u8] = &[b'a', 0xff];
+        assert!(matches!(strip_reader(invalid, Vec::new(), Builtin::Python), Err(Error::Decode { .. })));
+    }
+
+    #[test]
+    fn comment_rules() {
+        let purged = |src: &str, lang: Builtin| src.purge_commentaries(lang.comments()).collect::<Result<String, _>>().unwrap();
+        assert_eq!(purged("a = 1 -- one\n", Builtin::Lua), "a = 1 \n");
+        // the comments of languages with a scanner of their own are not described by rules
+        assert!(Builtin::Python.comments().is_empty());
+        assert_eq!(purged("'''a''' # b", Builtin::Python), "'''a''' # b");
+    }
+}
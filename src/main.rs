@@ -1,42 +1,174 @@
 use std::env;
-use std::fs::write;
-use walkdir::WalkDir;
-use crate::decomments::{proc_trimming, Type};
+use std::fs::{write, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use remove_commentary::{Builtin, Config, Registry, SqlDialect, Stripper, CONFIG_FILES};
+use walkdir::{DirEntry, WalkDir};
 
-mod decomments;
+// Directories of version control systems, which are never processed.
+const VCS_DIRS: [&str; 3] = [".git", ".hg", ".svn"];
+
+// Number of bytes read from the start of a file to identify its language.
+const SNIFF_LEN: u64 = 64 * 1024;
+
+// What to do when a file cannot be processed.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum ErrorPolicy {
+    FailFast,   // stop at the first failure without rewriting anything
+    Skip,       // report the failure, leave the file untouched and carry on
+    BestEffort, // recover from malformed input and rewrite the file anyway
+}
+
+impl ErrorPolicy {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fail-fast" => Some(ErrorPolicy::FailFast),
+            "skip" => Some(ErrorPolicy::Skip),
+            "best-effort" => Some(ErrorPolicy::BestEffort),
+            _ => None,
+        }
+    }
+
+    // Stops the run after a failure if this policy asks for it, before any file has been written.
+    fn after_failure(self) {
+        if self == ErrorPolicy::FailFast {
+            println!("*** Aborting, no file has been modified");
+            exit(1);
+        }
+    }
+}
+
+// Whether `entry` is the metadata directory of a version control system.
+fn is_vcs_dir(entry: &DirEntry) -> bool {
+    entry.depth() > 0 && entry.file_type().is_dir() && entry.file_name().to_str().is_some_and(|name| VCS_DIRS.contains(&name))
+}
+
+// Whether `entry` is a configuration file of this tool, which is never processed.
+fn is_config_file(entry: &DirEntry) -> bool {
+    entry.file_name().to_str().is_some_and(|name| CONFIG_FILES.contains(&name))
+}
+
+// Reads the start of the file at `path`, which is enough to identify its language.
+fn read_prefix(path: &Path) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    File::open(path)?.take(SNIFF_LEN).read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn usage(program: &str) -> ! {
+    println!("Usage: {} [--keep-docs] [--strip-legal] [--strip-directives] [--pass-docstrings] [--verify] [--on-error=fail-fast|skip|best-effort] [--config=<file>] [--sql-dialect=postgres|mysql|sqlite|tsql] [--explain] <path>", program);
+    exit(1);
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        println!("Usage: {} <path>", args[0]);
-        std::process::exit(1);
+    let mut keep_docs = false;
+    let mut keep_legal = true;
+    let mut keep_directives = true;
+    let mut pass_docstrings = false;
+    let mut verify = false;
+    let mut explain = false;
+    let mut policy = ErrorPolicy::Skip;
+    let mut config_path = None;
+    let mut sql_dialect = None;
+    let mut root_path = None;
+
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--keep-docs" => keep_docs = true,
+            "--strip-legal" => keep_legal = false,
+            "--strip-directives" => keep_directives = false,
+            "--pass-docstrings" => pass_docstrings = true,
+            "--verify" => verify = true,
+            "--explain" => explain = true,
+            _ if arg.starts_with("--on-error=") => {
+                policy = ErrorPolicy::parse(&arg["--on-error=".len()..]).unwrap_or_else(|| usage(&args[0]))
+            }
+            _ if arg.starts_with("--sql-dialect=") => {
+                sql_dialect = Some(SqlDialect::parse(&arg["--sql-dialect=".len()..]).unwrap_or_else(|| usage(&args[0])))
+            }
+            _ if arg.starts_with("--config=") => config_path = Some(&arg["--config=".len()..]),
+            _ if root_path.is_none() && !arg.starts_with("--") => root_path = Some(arg),
+            _ => usage(&args[0]),
+        }
+    }
+
+    let Some(root_path) = root_path else { usage(&args[0]) };
+
+    // Without --config, a configuration file in the processed directory or else in the working one is used.
+    let config = match config_path {
+        Some(path) => Some(Config::load(path)),
+        None => Config::find(root_path).or_else(|| Config::find(".")),
+    };
+    let mut registry = Registry::default();
+    if let Some(dialect) = sql_dialect {
+        registry.register(Builtin::Sql(dialect));
+    }
+    match config.transpose() {
+        Ok(config) => config.unwrap_or_default().register(&mut registry),
+        Err(e) => {
+            println!("*** Failed to load {}", e);
+            exit(1);
+        }
     }
 
-    let root_path = &args[1];
+    // Every file is processed before anything is written, so that a fail-fast run leaves the tree untouched.
+    let mut processed: Vec<(PathBuf, String)> = Vec::new();
+    let mut failures = 0;
 
-    for entry in WalkDir::new(root_path).into_iter().filter_map(|e| e.ok()) {
+    for entry in WalkDir::new(root_path).into_iter().filter_entry(|e| !is_vcs_dir(e)).filter_map(|e| e.ok()) {
         let file_path = entry.path();
-        if file_path.is_file() {
-            if let Some(extension) = file_path.extension().and_then(|s| s.to_str()) {
-                let lang_type = match extension {
-                    "c" | "cpp" | "cs" | "h" | "hpp" | "inl" | "rs" | "java" | "kt" => Type::RustC,
-                    "py" => Type::Python,
-                    "hs" => Type::Haskell,
-                    "htm" | "html" | "xml" => Type::Markup,
-                    _ => continue, // Skip files with other extensions
-                };
-
-                match proc_trimming(file_path.to_str().unwrap(), lang_type) {
-                    Ok(contents) => {
-                        if write(file_path, contents).is_ok() {
-                            println!("*** {} has been successfully processed", file_path.display());
-                        }
-                    }
-                    Err(_) => {
-                        println!("*** Failed to process {}", file_path.display());
-                    }
+        if file_path.is_file() && !is_config_file(&entry) {
+            let content = match read_prefix(file_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    println!("*** Failed to read {}: {}", file_path.display(), e);
+                    failures += 1;
+                    policy.after_failure();
+                    continue;
+                }
+            };
+            let identified = registry.identify(file_path, &content);
+            if explain {
+                match &identified {
+                    Some((lang, reason)) => println!("{}: {} ({})", file_path.display(), lang.name(), reason),
+                    None => println!("{}: no language, skipped", file_path.display()),
+                }
+                continue;
+            }
+            let Some((lang, _)) = identified else {
+                continue; // Skip files in other languages
+            };
+            let stripper = Stripper::shared(lang)
+                .keep_docs(keep_docs)
+                .keep_legal(keep_legal)
+                .keep_directives(keep_directives)
+                .pass_docstrings(pass_docstrings)
+                .verify(verify)
+                .recover(policy == ErrorPolicy::BestEffort);
+            match stripper.strip_file(file_path) {
+                Ok(contents) => processed.push((file_path.to_path_buf(), contents)),
+                Err(e) => {
+                    println!("*** Failed to process {}", e);
+                    failures += 1;
+                    policy.after_failure();
                 }
             }
         }
     }
+
+    for (file_path, contents) in processed {
+        match write(&file_path, contents) {
+            Ok(()) => println!("*** {} has been successfully processed", file_path.display()),
+            Err(e) => {
+                println!("*** Failed to write {}: {}", file_path.display(), e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        exit(1);
+    }
 }
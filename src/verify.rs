@@ -0,0 +1,182 @@
+use crate::decomments::Type;
+use roxmltree::{Document, ParsingOptions};
+use serde::Deserialize;
+use std::fmt::Display;
+
+// Checks that stripping `input` into `output` did not break a document that was valid before.
+// Returns a description of the problem otherwise; languages without a checker always pass.
+pub(crate) fn verify(lang: Type, input: &str, output: &str) -> Result<(), String> {
+    match lang {
+        Type::Xml => {
+            let options = || ParsingOptions { allow_dtd: true, ..ParsingOptions::default() };
+            if Document::parse_with_options(input, options()).is_err() {
+                return Ok(());
+            }
+            Document::parse_with_options(output, options())
+                .map(drop)
+                .map_err(|e| format!("output is no longer well-formed XML: {}", e))
+        }
+        Type::Yaml => same_data("YAML", yaml, input, output),
+        Type::Toml => same_data("TOML", |text| text.parse::<toml::Table>(), input, output),
+        Type::Ini => same_data("INI", ini, input, output),
+        Type::Properties => same_data("properties", |text| Ok::<_, String>(properties(text)), input, output),
+        Type::Dotenv => same_data(".env", dotenv, input, output),
+        _ => Ok(()),
+    }
+}
+
+// Checks that `output` holds the same data as `input` once read by `parse`, unless `input` cannot be read.
+fn same_data<T: PartialEq, E: Display>(format: &str, parse: impl Fn(&str) -> Result<T, E>, input: &str, output: &str) -> Result<(), String> {
+    let Ok(before) = parse(input) else { return Ok(()) };
+    match parse(output) {
+        Ok(after) if after == before => Ok(()),
+        Ok(_) => Err(format!("output no longer holds the same {} data", format)),
+        Err(e) => Err(format!("output is no longer valid {}: {}", format, e)),
+    }
+}
+
+// Reads every document of a YAML stream.
+fn yaml(text: &str) -> Result<Vec<serde_yaml::Value>, serde_yaml::Error> {
+    serde_yaml::Deserializer::from_str(text).map(serde_yaml::Value::deserialize).collect()
+}
+
+// Reads the entries of an INI file as section, key and value, where indented lines continue the
+// value above them.
+fn ini(text: &str) -> Result<Vec<(String, String, String)>, String> {
+    let mut section = String::new();
+    let mut entries: Vec<(String, String, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let body = line.trim();
+        if body.is_empty() || body.starts_with([';', '#']) {
+            continue;
+        }
+        if let Some(name) = body.strip_prefix('[').and_then(|name| name.strip_suffix(']')) {
+            section = name.trim().to_string();
+        } else if let Some((key, value)) = body.split_once(['=', ':']) {
+            entries.push((section.clone(), key.trim().to_string(), value.trim().to_string()));
+        } else if let Some(entry) = entries.last_mut().filter(|_| line.starts_with([' ', '\t'])) {
+            entry.2 = format!("{}\n{}", entry.2, body);
+        } else {
+            return Err(format!("line {} is neither a section nor an entry", i + 1));
+        }
+    }
+    Ok(entries)
+}
+
+// Reads the entries of a Java properties file as key and value, joining the lines that a trailing
+// backslash continues. Escapes are kept as they are.
+fn properties(text: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let mut logical = line.trim_start_matches([' ', '\t', '\x0c']).to_string();
+        if logical.is_empty() || logical.starts_with(['#', '!']) {
+            continue;
+        }
+        while (logical.len() - logical.trim_end_matches('\\').len()) % 2 == 1 {
+            logical.pop();
+            match lines.next() {
+                Some(next) => logical.push_str(next.trim_start_matches([' ', '\t', '\x0c'])),
+                None => break,
+            }
+        }
+        let mut escaped = false;
+        let end = logical
+            .char_indices()
+            .find(|&(_, c)| {
+                let separator = !escaped && (c == '=' || c == ':' || c.is_whitespace());
+                escaped = !escaped && c == '\\';
+                separator
+            })
+            .map_or(logical.len(), |(i, _)| i);
+        let value = logical[end..].trim_start();
+        let value = value.strip_prefix(['=', ':']).unwrap_or(value).trim_start();
+        entries.push((logical[..end].to_string(), value.to_string()));
+    }
+    entries
+}
+
+// Reads the variables of a `.env` file as name and value. Quoted values may span lines, and a `#`
+// after a blank ends an unquoted one.
+fn dotenv(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut variables = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let line_end = text[pos..].find('\n').map_or(text.len(), |i| pos + i);
+        let line = &text[pos..line_end];
+        pos = line_end + 1;
+        let body = line.trim();
+        if body.is_empty() || body.starts_with('#') {
+            continue;
+        }
+        let (name, value) = line.split_once('=').ok_or_else(|| format!("`{}` does not set a variable", body))?;
+        let name = name.trim().trim_start_matches("export ").trim_start().to_string();
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                // the value runs to the closing quote, maybe on a later line
+                let start = line_end - value.len() + 1;
+                let mut escaped = false;
+                let end = text[start..]
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        let close = !escaped && c == quote;
+                        escaped = !escaped && quote == '"' && c == '\\';
+                        close
+                    })
+                    .map(|(i, _)| start + i)
+                    .ok_or_else(|| format!("the value of `{}` is not closed", name))?;
+                pos = text[end..].find('\n').map_or(text.len(), |i| end + i + 1);
+                text[start..end].to_string()
+            }
+            _ => {
+                let end = value.match_indices([' ', '\t']).map(|(i, _)| i).find(|&i| value[i..].trim_start().starts_with('#'));
+                value[..end.unwrap_or(value.len())].trim_end().to_string()
+            }
+        };
+        variables.push((name, value));
+    }
+    Ok(variables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dotenv, ini, properties, verify};
+    use crate::decomments::Type;
+
+    #[test]
+    fn xml() {
+        assert!(verify(Type::Xml, "<a><!-- c --></a>", "<a></a>").is_ok());
+        assert!(verify(Type::Xml, "<a><!-- c --></a>", "<a>").is_err());
+        // malformed input is not checked
+        assert!(verify(Type::Xml, "<a>", "<a>").is_ok());
+        assert!(verify(Type::Rust, "fn a() {}", "fn a(").is_ok());
+    }
+
+    #[test]
+    fn stripper() {
+        let stripper = crate::Stripper::new(Type::Xml).verify(true);
+        assert_eq!(stripper.strip_str("<a><!-- c --><b/></a>").unwrap(), "<a><b/></a>");
+    }
+
+    #[test]
+    fn same_data() {
+        assert!(verify(Type::Toml, "a = 1 # c\n", "a = 1 \n").is_ok());
+        assert!(verify(Type::Toml, "a = 1\n", "a = 2\n").is_err());
+        assert!(verify(Type::Yaml, "a: 1 # c\n---\nb: 2\n", "a: 1 \n---\nb: 2\n").is_ok());
+        assert!(verify(Type::Yaml, "a: [1, 2]\n", "a: [1,\n").is_err());
+        assert!(verify(Type::Ini, "[a]\nb = 1 ; c\n", "[a]\nb = 1 \n").is_err());
+    }
+
+    #[test]
+    fn readers() {
+        let entries = ini("[a]\nb = 1\n  2\n; c\n").unwrap();
+        assert_eq!(entries, [("a".to_string(), "b".to_string(), "1\n2".to_string())]);
+        assert!(ini("[a]\nnot an entry\n").is_err());
+        let entries = properties("a\\ b = c \\\n  d\n# e\nf:g\n");
+        assert_eq!(entries, [("a\\ b".to_string(), "c d".to_string()), ("f".to_string(), "g".to_string())]);
+        let variables = dotenv("export A=b #c\nB='x\ny' # z\n").unwrap();
+        assert_eq!(variables, [("A".to_string(), "b".to_string()), ("B".to_string(), "x\ny".to_string())]);
+        assert!(dotenv("C=\"open\n").is_err());
+    }
+}
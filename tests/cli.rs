@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Creates a fresh directory holding `files`, given as name and content.
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("remove-commentary-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (file, content) in files {
+        fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+        fs::write(dir.join(file), content).unwrap();
+    }
+    dir
+}
+
+// Runs the tool over `dir` and returns whether it succeeded.
+fn run(dir: &Path, args: &[&str]) -> bool {
+    Command::new(env!("CARGO_BIN_EXE_RemoveCommentary")).args(args).arg(dir).output().unwrap().status.success()
+}
+
+fn read(dir: &Path, file: &str) -> String {
+    fs::read_to_string(dir.join(file)).unwrap()
+}
+
+const GOOD: &str = "fn a() {} // c\n";
+const BAD: &str = "fn b() {} /* open\n";
+
+#[test]
+fn fail_fast_leaves_the_tree_untouched() {
+    let dir = tree("fail-fast", &[("a.rs", GOOD), ("b.rs", BAD)]);
+    assert!(!run(&dir, &["--on-error=fail-fast"]));
+    assert_eq!(read(&dir, "a.rs"), GOOD);
+    assert_eq!(read(&dir, "b.rs"), BAD);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn skip_leaves_failed_files_alone() {
+    let dir = tree("skip", &[("a.rs", GOOD), ("b.rs", BAD)]);
+    assert!(!run(&dir, &[]));
+    assert_eq!(read(&dir, "a.rs"), "fn a() {} \n");
+    assert_eq!(read(&dir, "b.rs"), BAD);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn best_effort_rewrites_everything() {
+    let dir = tree("best-effort", &[("a.rs", GOOD), ("b.rs", BAD)]);
+    assert!(run(&dir, &["--on-error=best-effort"]));
+    assert_eq!(read(&dir, "a.rs"), "fn a() {} \n");
+    assert_eq!(read(&dir, "b.rs"), "fn b() {} ");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unknown_policy() {
+    let dir = tree("unknown-policy", &[("a.rs", GOOD)]);
+    assert!(!run(&dir, &["--on-error=maybe"]));
+    assert_eq!(read(&dir, "a.rs"), GOOD);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn explain_leaves_files_alone() {
+    let dir = tree("explain", &[("a.rs", GOOD), ("run", "#!/usr/bin/env python3\n# c\n"), ("notes.txt", "x\n")]);
+    let output = Command::new(env!("CARGO_BIN_EXE_RemoveCommentary")).arg("--explain").arg(&dir).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("a.rs: Rust (file name)"));
+    assert!(stdout.contains("run: Python (shebang runs \"python3\")"));
+    assert!(stdout.contains("notes.txt: no language, skipped"));
+    assert_eq!(read(&dir, "a.rs"), GOOD);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn version_control_directories_are_skipped() {
+    let dir = tree("vcs", &[("a.rs", GOOD), (".git/hooks/b.rs", GOOD), ("src/.hg/c.rs", GOOD)]);
+    assert!(run(&dir, &[]));
+    assert_eq!(read(&dir, "a.rs"), "fn a() {} \n");
+    assert_eq!(read(&dir, ".git/hooks/b.rs"), GOOD);
+    assert_eq!(read(&dir, "src/.hg/c.rs"), GOOD);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn configuration_files_are_left_alone() {
+    let config = "# c\n[[language]]\nname = \"Cfg\"\nfiles = [\"config/*.cfg\"]\nline_comments = [\";\"]\n";
+    let dir = tree("config", &[(".removecommentary.toml", config), ("config/a.cfg", "a ; c\n"), ("b.cfg", "b ; c\n")]);
+    assert!(run(&dir, &[]));
+    assert_eq!(read(&dir, ".removecommentary.toml"), config);
+    assert_eq!(read(&dir, "config/a.cfg"), "a \n");
+    assert_eq!(read(&dir, "b.cfg"), "b ; c\n");
+    fs::remove_dir_all(dir).unwrap();
+}